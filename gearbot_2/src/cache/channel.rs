@@ -38,7 +38,7 @@ impl Channel {
                 thread_meta: None,
             },
             GuildChannel::NewsThread(thread) => Channel {
-                channel_type: ChannelType::GuildPrivateThread,
+                channel_type: ChannelType::GuildNewsThread,
                 permission_overwrites: Vec::new(),
                 name: thread.name,
                 topic: None,
@@ -62,7 +62,7 @@ impl Channel {
                 thread_meta: Some(thread.thread_metadata),
             },
             GuildChannel::PublicThread(thread) => Channel {
-                channel_type: ChannelType::GuildPrivateThread,
+                channel_type: ChannelType::GuildPublicThread,
                 permission_overwrites: Vec::new(),
                 name: thread.name,
                 topic: None,
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::mem;
 use std::sync::Arc;
 
 use parking_lot::RwLock;
@@ -15,7 +16,7 @@ use twilight_model::voice::VoiceState as TwilightVoiceState;
 use gearbot_2_lib::util::markers::{ChannelId, EmojiId, GuildId, RoleId, UserId};
 
 use crate::cache::voice_state::VoiceState;
-use crate::cache::{Channel, Emoji, Member, Role};
+use crate::cache::{Channel, Emoji, Invite, Member, Role};
 use crate::{Cache, Metrics};
 
 #[derive(Clone, Eq, PartialEq)]
@@ -57,6 +58,10 @@ pub struct Guild {
     pub nsfw: NSFWLevel,
     members: Arc<RwLock<HashMap<UserId, Arc<Member>>>>,
     voice_states: Arc<RwLock<HashMap<UserId, Arc<VoiceState>>>>,
+    // only loaded once something needs it, kept up to date by the ban events after that
+    bans: Arc<RwLock<Option<HashSet<UserId>>>>,
+    // only tracked for guilds that log member joins, keyed by invite code
+    invites: Arc<RwLock<Option<HashMap<String, Invite>>>>,
 
     cache_state: RwLock<GuildCacheState>,
 }
@@ -74,15 +79,17 @@ impl From<TwilightGuild> for Guild {
             features: guild.features,
             mfa: guild.mfa_level,
             channels: Arc::new(RwLock::new(convert_channels(guild.channels))),
-            presence_limit: 0,
-            max_members: 0,
-            vanity_invite: None,
-            description: None,
-            banner: None,
-            guild_locale: "".to_string(),
-            nsfw: NSFWLevel::Default,
+            presence_limit: guild.max_presences.unwrap_or_default(),
+            max_members: guild.max_members.unwrap_or_default(),
+            vanity_invite: guild.vanity_url_code,
+            description: guild.description,
+            banner: guild.banner,
+            guild_locale: guild.preferred_locale,
+            nsfw: guild.nsfw_level,
             members: Default::default(),
             voice_states: Arc::new(RwLock::new(convert_voice_states(guild.voice_states))),
+            bans: Default::default(),
+            invites: Default::default(),
             cache_state: RwLock::new(GuildCacheState::Created),
         }
     }
@@ -110,6 +117,8 @@ impl Guild {
             nsfw: new.nsfw_level,
             members: old.members.clone(),
             voice_states: old.voice_states.clone(),
+            bans: old.bans.clone(),
+            invites: old.invites.clone(),
             cache_state: RwLock::new(old.cache_state.read().clone()),
         }
     }
@@ -118,6 +127,10 @@ impl Guild {
         self.roles.write().insert(role.id, role)
     }
 
+    pub fn get_role(&self, role_id: &RoleId) -> Option<Arc<Role>> {
+        self.roles.read().get(role_id).cloned()
+    }
+
     pub fn remove_role(&self, role_id: &RoleId) -> Option<Arc<Role>> {
         self.roles.write().remove(role_id)
     }
@@ -190,10 +203,58 @@ impl Guild {
         self.members.read().len()
     }
 
+    /// Checks the ban list, returns none if it hasn't been loaded yet
+    pub fn is_banned(&self, user_id: &UserId) -> Option<bool> {
+        self.bans.read().as_ref().map(|bans| bans.contains(user_id))
+    }
+
+    pub fn set_bans(&self, bans: HashSet<UserId>) {
+        *self.bans.write() = Some(bans);
+    }
+
+    /// Only updates the ban list if it was loaded, otherwise it will pick this up when it does get loaded
+    pub fn add_ban(&self, user_id: UserId) {
+        if let Some(bans) = self.bans.write().as_mut() {
+            bans.insert(user_id);
+        }
+    }
+
+    pub fn remove_ban(&self, user_id: &UserId) {
+        if let Some(bans) = self.bans.write().as_mut() {
+            bans.remove(user_id);
+        }
+    }
+
+    /// Replace the tracked invites, returning the previous ones if there where any
+    pub fn set_invites(&self, invites: HashMap<String, Invite>) -> Option<HashMap<String, Invite>> {
+        self.invites.write().replace(invites)
+    }
+
+    pub fn is_tracking_invites(&self) -> bool {
+        self.invites.read().is_some()
+    }
+
+    /// Only updates the invites if we are tracking them for this guild
+    pub fn insert_invite(&self, invite: Invite) {
+        if let Some(invites) = self.invites.write().as_mut() {
+            invites.insert(invite.code.clone(), invite);
+        }
+    }
+
+    pub fn remove_invite(&self, code: &str) {
+        if let Some(invites) = self.invites.write().as_mut() {
+            invites.remove(code);
+        }
+    }
+
     pub fn insert_channel(&self, channel_id: ChannelId, channel: Arc<Channel>) -> Option<Arc<Channel>> {
         self.channels.write().insert(channel_id, channel)
     }
 
+    pub fn get_channel(&self, channel_id: &ChannelId) -> Option<Arc<Channel>> {
+        self.channels.read().get(channel_id).cloned()
+    }
+
     pub fn remove_channel(&self, channel_id: &ChannelId) -> Option<Arc<Channel>> {
         self.channels.write().remove(channel_id)
     }
@@ -210,8 +271,14 @@ impl Guild {
         *self.cache_state.read() == state
     }
 
-    pub fn update_emoji(&self, emoji: Vec<TwilightEmoji>) {
-        *self.emoji.write() = convert_emoji(emoji)
+    /// Replace all emoji, returns the old and new emoji so the caller can see what changed
+    pub fn update_emoji(
+        &self,
+        emoji: Vec<TwilightEmoji>,
+    ) -> (HashMap<EmojiId, Arc<Emoji>>, HashMap<EmojiId, Arc<Emoji>>) {
+        let new = convert_emoji(emoji);
+        let old = mem::replace(&mut *self.emoji.write(), new.clone());
+        (old, new)
     }
 
     pub fn get_emoji_count(&self) -> usize {
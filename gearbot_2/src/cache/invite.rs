@@ -0,0 +1,40 @@
+use twilight_model::gateway::payload::incoming::InviteCreate;
+use twilight_model::invite::Invite as TwilightInvite;
+
+use gearbot_2_lib::util::markers::UserId;
+
+#[derive(Clone)]
+pub struct Invite {
+    pub code: String,
+    pub inviter: Option<UserId>,
+    pub uses: u64,
+    // 0 means unlimited
+    pub max_uses: u64,
+}
+
+impl Invite {
+    pub fn from_invite(invite: TwilightInvite) -> Self {
+        Invite {
+            code: invite.code,
+            inviter: invite.inviter.map(|user| user.id),
+            uses: invite.uses.unwrap_or_default(),
+            max_uses: invite.max_uses.unwrap_or_default(),
+        }
+    }
+
+    pub fn from_create(invite: &InviteCreate) -> Self {
+        Invite {
+            code: invite.code.clone(),
+            inviter: invite.inviter.as_ref().map(|user| user.id),
+            uses: 0,
+            max_uses: invite.max_uses,
+        }
+    }
+}
+
+/// The invite a member most likely joined through
+pub struct UsedInvite {
+    pub code: String,
+    pub inviter: Option<UserId>,
+    pub vanity: bool,
+}
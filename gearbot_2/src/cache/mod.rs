@@ -7,6 +7,7 @@ pub use channel::Channel;
 pub use emoji::Emoji;
 use gearbot_2_lib::util::markers::{GuildId, UserId};
 pub use guild::Guild;
+pub use invite::Invite;
 pub use member::Member;
 pub use role::Role;
 pub use user::User;
@@ -14,6 +15,7 @@ pub use user::User;
 pub mod channel;
 pub mod emoji;
 pub mod guild;
+pub mod invite;
 pub mod member;
 pub mod role;
 pub mod user;
@@ -0,0 +1,61 @@
+use tracing::{error, warn};
+
+use gearbot_2_lib::kafka::message::ComponentInteraction;
+use gearbot_2_lib::util::custom_id::{self, CustomId};
+use gearbot_2_lib::util::error::GearError;
+use gearbot_2_lib::util::GearResult;
+
+use crate::util::bot_context::Context;
+
+mod verification;
+
+pub type ComponentResult = GearResult<()>;
+
+pub async fn handle(token: String, locale: String, component: ComponentInteraction, context: Context) {
+    // the api already parsed this one before sending it our way
+    let custom_id = match CustomId::parse(&component.custom_id) {
+        Some(custom_id) => custom_id,
+        None => {
+            warn!(
+                "Received a component interaction with an unreadable custom id: {:?}",
+                component
+            );
+            return;
+        }
+    };
+
+    let result = match custom_id.prefix.as_str() {
+        custom_id::VERIFY => verification::run(&component, &custom_id, &token, &locale, &context).await,
+        _ => {
+            warn!(
+                "Received a component interaction we have no handler for: {:?}",
+                component
+            );
+            return;
+        }
+    };
+
+    if let Err(error) = result {
+        if !error.is_user_error() {
+            error!(
+                "Failed to handle component interaction: {} (interaction data: {:?})",
+                error.get_log_error(),
+                &component
+            );
+        }
+        if let Err(e) = inform_failure(&error, &token, &locale, &context).await {
+            error!("Failed to inform user of this failure! {}", e.get_log_error())
+        }
+    }
+}
+
+async fn inform_failure(error: &GearError, token: &str, locale: &str, context: &Context) -> GearResult<()> {
+    context
+        .interaction_client()
+        .create_followup_message(token)
+        .content(&error.get_user_error(&context.translator, locale))?
+        .ephemeral(true)
+        .exec()
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use gearbot_2_lib::kafka::message::ComponentInteraction;
+use gearbot_2_lib::translations::GearBotLangKey;
+use gearbot_2_lib::util::custom_id::CustomId;
+use gearbot_2_lib::util::markers::{GuildId, UserId};
+
+use crate::communication::component::ComponentResult;
+use crate::moderation::verification::{self, VerifyOutcome};
+use crate::util::bot_context::Context;
+
+pub async fn run(
+    component: &ComponentInteraction,
+    custom_id: &CustomId,
+    token: &str,
+    locale: &str,
+    context: &Context,
+) -> ComponentResult {
+    // answers to the challenge carry the index of the one they picked
+    let answer = custom_id.args.first().and_then(|answer| answer.parse().ok());
+    let outcome = verification::verify(
+        GuildId::new(component.guild_id),
+        UserId::new(component.user_id),
+        answer,
+        context,
+    )
+    .await?;
+
+    let key = match outcome {
+        VerifyOutcome::Challenge { animal, components } => {
+            let content = context
+                .translator
+                .translate(locale, GearBotLangKey::VerificationChallenge)
+                .arg("thing", animal)
+                .build()
+                .to_string();
+            context
+                .interaction_client()
+                .create_followup_message(token)
+                .content(&content)?
+                .components(&components)?
+                .exec()
+                .await?;
+            return Ok(());
+        }
+        VerifyOutcome::NotConfigured => GearBotLangKey::VerificationNotConfigured,
+        VerifyOutcome::AlreadyVerified => GearBotLangKey::VerificationAlreadyVerified,
+        VerifyOutcome::AccountTooNew { hours } => {
+            let content = context
+                .translator
+                .translate(locale, GearBotLangKey::VerificationAccountTooNew)
+                .arg("hours", hours)
+                .build()
+                .to_string();
+            return reply(answer.is_some(), &content, token, context).await;
+        }
+        VerifyOutcome::WrongAnswer => GearBotLangKey::VerificationWrongAnswer,
+        VerifyOutcome::Expired => GearBotLangKey::VerificationExpired,
+        VerifyOutcome::Verified => GearBotLangKey::VerificationSucceeded,
+        VerifyOutcome::Failed => GearBotLangKey::VerificationFailed,
+    };
+
+    let content = context.translator.translate(locale, key).build().to_string();
+    reply(answer.is_some(), &content, token, context).await
+}
+
+/// Answers replace the challenge they belong to, clicks on the panel get a message of their own
+async fn reply(answered: bool, content: &str, token: &str, context: &Context) -> ComponentResult {
+    let client = context.interaction_client();
+    if answered {
+        client
+            .update_interaction_original(token)
+            .content(Some(content))?
+            .components(Some(&[]))?
+            .exec()
+            .await?;
+    } else {
+        client.create_followup_message(token).content(content)?.exec().await?;
+    }
+    Ok(())
+}
@@ -5,7 +5,9 @@ use gearbot_2_lib::kafka::message::InteractionCommand;
 use gearbot_2_lib::util::GearResult;
 
 mod debug;
+mod raid;
 mod userinfo;
+mod verification;
 
 pub type InteractionResult = GearResult<()>;
 
@@ -17,6 +19,10 @@ pub async fn handle(token: String, locale: String, command: InteractionCommand,
         InteractionCommand::Userinfo { user_id, guild_id } => {
             userinfo::run(*user_id, *guild_id, &token, &locale, &context).await
         }
+        InteractionCommand::EndRaid { guild_id } => raid::end(*guild_id, &token, &locale, &context).await,
+        InteractionCommand::VerificationPanel { guild_id, channel_id } => {
+            verification::panel(*guild_id, *channel_id, &token, &locale, &context).await
+        }
     };
 
     if let Err(error) = result {
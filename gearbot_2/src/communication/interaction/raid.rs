@@ -0,0 +1,57 @@
+use twilight_http::request::AttachmentFile;
+
+use gearbot_2_lib::translations::GearBotLangKey;
+use gearbot_2_lib::util::markers::GuildId;
+
+use crate::communication::interaction::InteractionResult;
+use crate::moderation::raid::end_raid;
+use crate::util::bot_context::Context;
+
+pub async fn end(guild_id: u64, token: &str, locale: &str, context: &Context) -> InteractionResult {
+    let guild_id = GuildId::new(guild_id);
+
+    let raid = match end_raid(&guild_id, context).await {
+        Some(raid) => raid,
+        None => {
+            context
+                .interaction_client()
+                .create_followup_message(token)
+                .content(
+                    &context
+                        .translator
+                        .translate(locale, GearBotLangKey::RaidNotActive)
+                        .build(),
+                )?
+                .exec()
+                .await?;
+            return Ok(());
+        }
+    };
+
+    // can easily be more then fits in a message, send the full list as file
+    let accounts = raid
+        .members
+        .iter()
+        .map(|user_id| match context.cache.get_user(user_id) {
+            Some(user) => format!("{} {}", user_id, user),
+            None => user_id.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    context
+        .interaction_client()
+        .create_followup_message(token)
+        .content(
+            &context
+                .translator
+                .translate(locale, GearBotLangKey::RaidEnded)
+                .arg("count", raid.members.len())
+                .build(),
+        )?
+        .attach(&[AttachmentFile::from_bytes("raid_accounts.txt", accounts.as_bytes())])
+        .exec()
+        .await?;
+
+    Ok(())
+}
@@ -35,6 +35,25 @@ pub async fn run(user_id: u64, guild_id: u64, token: &str, locale: &str, context
             },
         );
 
+        let mut description = context
+            .translator
+            .translate(locale, GearBotLangKey::UserinfoUser)
+            .arg("id", user_id.get())
+            .arg("created_on", formatted_snowflake_timestamp(&user_id))
+            .arg("age", snowflake_age(&user_id, 2, locale, &context.translator))
+            .build()
+            .to_string();
+
+        let ban_status = match context.is_banned(&guild_id, &user_id).await {
+            Some(true) => Some(GearBotLangKey::UserinfoBanned),
+            Some(false) => None,
+            None => Some(GearBotLangKey::UserinfoBanUnknown),
+        };
+        if let Some(key) = ban_status {
+            description.push('\n');
+            description.push_str(&context.translator.translate(locale, key).build());
+        }
+
         builder = builder
             .author(
                 EmbedAuthorBuilder::new(
@@ -45,15 +64,7 @@ pub async fn run(user_id: u64, guild_id: u64, token: &str, locale: &str, context
                 .icon_url(ImageSource::url(user_avatar)?),
             )
             .thumbnail(ImageSource::url(big_avatar)?)
-            .description(
-                context
-                    .translator
-                    .translate(locale, GearBotLangKey::UserinfoUser)
-                    .arg("id", user_id.get())
-                    .arg("created_on", formatted_snowflake_timestamp(&user_id))
-                    .arg("age", snowflake_age(&user_id, 2, locale, &context.translator))
-                    .build(),
-            );
+            .description(description);
 
         context
             .interaction_client()
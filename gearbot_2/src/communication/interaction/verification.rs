@@ -0,0 +1,23 @@
+use gearbot_2_lib::translations::GearBotLangKey;
+use gearbot_2_lib::util::markers::{ChannelId, GuildId};
+
+use crate::communication::interaction::InteractionResult;
+use crate::moderation::verification;
+use crate::util::bot_context::Context;
+
+pub async fn panel(guild_id: u64, channel_id: u64, token: &str, locale: &str, context: &Context) -> InteractionResult {
+    let key = if verification::post_panel(&GuildId::new(guild_id), &ChannelId::new(channel_id), context).await? {
+        GearBotLangKey::VerificationPanelPosted
+    } else {
+        GearBotLangKey::VerificationNotConfigured
+    };
+
+    context
+        .interaction_client()
+        .create_followup_message(token)
+        .content(&context.translator.translate(locale, key).build())?
+        .exec()
+        .await?;
+
+    Ok(())
+}
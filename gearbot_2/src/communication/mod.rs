@@ -15,6 +15,7 @@ use gearbot_2_lib::kafka::sender::{KafkaSender, KafkaSenderError};
 
 use crate::util::bot_context::{BotStatus, Context};
 
+mod component;
 mod general;
 mod interaction;
 
@@ -144,7 +145,22 @@ fn handle_message(message: Message, context: Context) {
         Message::General(message) => general::handle(message, context),
         Message::Interaction { token, locale, command } => {
             if context.is_status(BotStatus::Primary) {
-                tokio::spawn(interaction::handle(token, locale, command, context));
+                context
+                    .tasks
+                    .clone()
+                    .spawn(interaction::handle(token, locale, command, context));
+            }
+        }
+        Message::Component {
+            token,
+            locale,
+            component,
+        } => {
+            if context.is_status(BotStatus::Primary) {
+                context
+                    .tasks
+                    .clone()
+                    .spawn(component::handle(token, locale, component, context));
             }
         }
     }
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use twilight_model::gateway::payload::incoming::{BanAdd, BanRemove};
+use twilight_model::guild::audit_log::AuditLogEventType;
+
+use gearbot_2_lib::datastore::guild::{GuildDatastore, InfractionType, LogCategory};
+use gearbot_2_lib::util::markers::{GuildId, RoleId, UserId};
+use gearbot_2_lib::util::url::assemble_user_avatar;
+use gearbot_2_lib::util::GearResult;
+
+use crate::cache::User;
+use crate::events::async_wrapper;
+use crate::logging::{name_and_id, LogEntry, COLOR_NEGATIVE, COLOR_POSITIVE};
+use crate::moderation::escalation::record_infraction;
+use crate::util::bot_context::Context;
+
+pub fn on_ban_add(ban: BanAdd, context: &Context) {
+    let user_id = ban.user.id;
+    let roles = match context.cache.get_guild(&ban.guild_id) {
+        Some(guild) => {
+            guild.add_ban(user_id);
+            // the member might already be gone from the cache depending on what event arrived first
+            guild
+                .get_member(&user_id)
+                .map(|member| member.roles.clone())
+                .unwrap_or_default()
+        }
+        None => Vec::new(),
+    };
+
+    async_wrapper(
+        process_ban(
+            ban.guild_id,
+            user_id,
+            Arc::new(User::assemble(ban.user, None)),
+            roles,
+            context.clone(),
+        ),
+        "ban_add",
+        context,
+    );
+}
+
+pub fn on_ban_remove(unban: BanRemove, context: &Context) {
+    let user_id = unban.user.id;
+    if let Some(guild) = context.cache.get_guild(&unban.guild_id) {
+        guild.remove_ban(&user_id);
+    }
+
+    async_wrapper(
+        process_unban(
+            unban.guild_id,
+            user_id,
+            Arc::new(User::assemble(unban.user, None)),
+            context.clone(),
+        ),
+        "ban_remove",
+        context,
+    );
+}
+
+async fn process_ban(
+    guild_id: GuildId,
+    user_id: UserId,
+    user: Arc<User>,
+    roles: Vec<RoleId>,
+    context: Context,
+) -> GearResult<()> {
+    // always look this one up, even without logs it's needed for the moderation history
+    let attribution = context
+        .attribute(&guild_id, &[AuditLogEventType::MemberBanAdd], user_id.cast())
+        .await;
+
+    let entry = LogEntry::new(LogCategory::Moderation, "🔨", "Member banned", COLOR_NEGATIVE)
+        .user(user_id, user.bot, &roles)
+        .field("User", name_and_id(&user, user_id))
+        .thumbnail(assemble_user_avatar(&user_id, user.discriminator, user.avatar.as_ref()))
+        .attribution(attribution.as_ref());
+    context.log(&guild_id, entry).await;
+
+    // bans we issued ourselves are already recorded, along with what led to them
+    if context.take_issued_ban(&guild_id, &user_id) {
+        return Ok(());
+    }
+
+    // bans done by hand are part of the moderation history as well
+    record_infraction(
+        &guild_id,
+        &user_id,
+        attribution.as_ref().map(|attribution| &attribution.moderator),
+        InfractionType::Ban,
+        attribution
+            .as_ref()
+            .and_then(|attribution| attribution.reason.as_deref()),
+        &[],
+        &context,
+    )
+    .await
+}
+
+async fn process_unban(guild_id: GuildId, user_id: UserId, user: Arc<User>, context: Context) -> GearResult<()> {
+    let attribution = context
+        .attribute(&guild_id, &[AuditLogEventType::MemberBanRemove], user_id.cast())
+        .await;
+
+    let entry = LogEntry::new(LogCategory::Moderation, "🕊️", "Member unbanned", COLOR_POSITIVE)
+        .user(user_id, user.bot, &[])
+        .field("User", name_and_id(&user, user_id))
+        .thumbnail(assemble_user_avatar(&user_id, user.discriminator, user.avatar.as_ref()))
+        .attribution(attribution.as_ref());
+    context.log(&guild_id, entry).await;
+
+    let info = context.get_guild_info(&guild_id).await?;
+    GuildDatastore::new(&context.datastore, &info.encryption_key, &guild_id)
+        .deactivate_infractions(&user_id, InfractionType::Ban)
+        .await?;
+    record_infraction(
+        &guild_id,
+        &user_id,
+        attribution.as_ref().map(|attribution| &attribution.moderator),
+        InfractionType::Unban,
+        attribution
+            .as_ref()
+            .and_then(|attribution| attribution.reason.as_deref()),
+        &[],
+        &context,
+    )
+    .await
+}
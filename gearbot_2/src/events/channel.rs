@@ -2,23 +2,34 @@ use std::sync::Arc;
 
 use tracing::error;
 use twilight_model::channel::Channel as TwilightChannel;
+use twilight_model::guild::audit_log::AuditLogEventType;
 
+use gearbot_2_lib::datastore::guild::LogCategory;
 use gearbot_2_lib::util::markers::{ChannelId, GuildId};
 
 use crate::cache::Channel;
+use crate::logging::permissions::overwrite_changes;
+use crate::logging::{
+    channel_name, channel_type_name, name_and_id, LogEntry, COLOR_NEGATIVE, COLOR_NEUTRAL, COLOR_POSITIVE,
+};
 use crate::util::bot_context::Context;
 
 pub fn on_channel_create(channel: TwilightChannel, context: &Context) {
-    if let Some(_new) = cache_channel_create(channel, context) {}
+    let channel_id = channel.id();
+    if let Some((guild_id, new)) = cache_channel_create(channel, context) {
+        context
+            .tasks
+            .spawn(log_channel_create(guild_id, channel_id, new, context.clone()));
+    }
 }
 
-pub fn cache_channel_create(channel: TwilightChannel, context: &Context) -> Option<Arc<Channel>> {
+pub fn cache_channel_create(channel: TwilightChannel, context: &Context) -> Option<(GuildId, Arc<Channel>)> {
     let channel_id = channel.id();
     if let TwilightChannel::Guild(guild_channel) = channel {
         if let Some(guild_id) = guild_channel.guild_id() {
             let new: Arc<Channel> = Arc::new(Channel::from_guild_channel(guild_channel));
             context.cache.insert_channel(guild_id, channel_id, new.clone());
-            return Some(new);
+            return Some((guild_id, new));
         } else {
             error!(
                 "Received a guild channel without guild id from twilight: {}",
@@ -32,7 +43,12 @@ pub fn cache_channel_create(channel: TwilightChannel, context: &Context) -> Opti
 pub fn on_channel_delete(channel: TwilightChannel, context: &Context) {
     if let TwilightChannel::Guild(guild_channel) = channel {
         if let Some(guild_id) = guild_channel.guild_id() {
-            if let Some(_old) = cache_channel_delete(&guild_id, &guild_channel.id(), context) {}
+            let channel_id = guild_channel.id();
+            if let Some(old) = cache_channel_delete(&guild_id, &channel_id, context) {
+                context
+                    .tasks
+                    .spawn(log_channel_delete(guild_id, channel_id, old, context.clone()));
+            }
         } else {
             error!("Received a guild channel delete without a guild id!")
         }
@@ -44,19 +60,24 @@ pub fn cache_channel_delete(guild_id: &GuildId, channel_id: &ChannelId, context:
 }
 
 pub fn on_channel_update(channel: TwilightChannel, context: &Context) {
-    if let Some((_old, _new)) = cache_channel_update(channel, context) {}
+    let channel_id = channel.id();
+    if let Some((guild_id, Some(old), new)) = cache_channel_update(channel, context) {
+        context
+            .tasks
+            .spawn(log_channel_update(guild_id, channel_id, old, new, context.clone()));
+    }
 }
 
 pub fn cache_channel_update(
     channel: TwilightChannel,
     context: &Context,
-) -> Option<(Option<Arc<Channel>>, Arc<Channel>)> {
+) -> Option<(GuildId, Option<Arc<Channel>>, Arc<Channel>)> {
     let channel_id = channel.id();
     if let TwilightChannel::Guild(guild_channel) = channel {
         if let Some(guild_id) = guild_channel.guild_id() {
             let new: Arc<Channel> = Arc::new(Channel::from_guild_channel(guild_channel));
             let old = context.cache.insert_channel(guild_id, channel_id, new.clone());
-            return Some((old, new));
+            return Some((guild_id, old, new));
         } else {
             error!(
                 "Received a guild channel without guild id from twilight: {}",
@@ -66,3 +87,137 @@ pub fn cache_channel_update(
     }
     None
 }
+
+async fn log_channel_create(guild_id: GuildId, channel_id: ChannelId, channel: Arc<Channel>, context: Context) {
+    let mut entry = LogEntry::new(LogCategory::Channels, "🆕", "Channel created", COLOR_POSITIVE)
+        .channel(channel_id)
+        .field("Channel", name_and_id(&channel.name, channel_id))
+        .field("Type", channel_type_name(channel.channel_type));
+
+    if let Some(guild) = context.cache.get_guild(&guild_id) {
+        if let Some(parent_id) = &channel.parent_id {
+            entry = entry.field("Category", channel_name(&guild, parent_id));
+        }
+        for change in overwrite_changes(&guild, &context.cache, &[], &channel.permission_overwrites) {
+            entry = entry.line(change);
+        }
+    }
+
+    let attribution = context
+        .attribute_log(
+            &guild_id,
+            LogCategory::Channels,
+            &[AuditLogEventType::ChannelCreate],
+            channel_id.cast(),
+        )
+        .await;
+    context.log(&guild_id, entry.attribution(attribution.as_ref())).await;
+}
+
+async fn log_channel_delete(guild_id: GuildId, channel_id: ChannelId, channel: Arc<Channel>, context: Context) {
+    let attribution = context
+        .attribute_log(
+            &guild_id,
+            LogCategory::Channels,
+            &[AuditLogEventType::ChannelDelete],
+            channel_id.cast(),
+        )
+        .await;
+    let entry = LogEntry::new(LogCategory::Channels, "🗑️", "Channel deleted", COLOR_NEGATIVE)
+        .channel(channel_id)
+        .field("Channel", name_and_id(&channel.name, channel_id))
+        .field("Type", channel_type_name(channel.channel_type))
+        .attribution(attribution.as_ref());
+
+    context.log(&guild_id, entry).await;
+}
+
+async fn log_channel_update(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    old: Arc<Channel>,
+    new: Arc<Channel>,
+    context: Context,
+) {
+    let guild = match context.cache.get_guild(&guild_id) {
+        Some(guild) => guild,
+        None => return,
+    };
+
+    let mut changes = Vec::new();
+    if old.name != new.name {
+        changes.push(format!("**Name**: {} ➡ {}", old.name, new.name));
+    }
+    if old.topic != new.topic {
+        changes.push(format!(
+            "**Topic**: {} ➡ {}",
+            old.topic.as_deref().unwrap_or("*None*"),
+            new.topic.as_deref().unwrap_or("*None*")
+        ));
+    }
+    if old.nsfw != new.nsfw {
+        changes.push(format!("**NSFW**: {} ➡ {}", old.nsfw, new.nsfw));
+    }
+    if old.user_rate_limit != new.user_rate_limit {
+        changes.push(format!(
+            "**Slowmode**: {}s ➡ {}s",
+            old.user_rate_limit, new.user_rate_limit
+        ));
+    }
+    if old.bitrate != new.bitrate {
+        changes.push(format!("**Bitrate**: {} ➡ {}", old.bitrate, new.bitrate));
+    }
+    if old.user_limit != new.user_limit {
+        changes.push(format!("**User limit**: {} ➡ {}", old.user_limit, new.user_limit));
+    }
+    if old.parent_id != new.parent_id {
+        let describe = |parent_id: &Option<ChannelId>| {
+            parent_id.map_or_else(|| "*None*".to_string(), |id| channel_name(&guild, &id))
+        };
+        changes.push(format!(
+            "**Category**: {} ➡ {}",
+            describe(&old.parent_id),
+            describe(&new.parent_id)
+        ));
+    }
+
+    let overwrites = overwrite_changes(
+        &guild,
+        &context.cache,
+        &old.permission_overwrites,
+        &new.permission_overwrites,
+    );
+
+    // position changes and the like are not logged
+    if changes.is_empty() && overwrites.is_empty() {
+        return;
+    }
+
+    let mut entry = LogEntry::new(LogCategory::Channels, "📝", "Channel updated", COLOR_NEUTRAL)
+        .channel(channel_id)
+        .field("Channel", name_and_id(&new.name, channel_id));
+    for change in changes {
+        entry = entry.line(change);
+    }
+    if !overwrites.is_empty() {
+        entry = entry.line("**Permission overwrites**:");
+        for change in overwrites {
+            entry = entry.line(change);
+        }
+    }
+
+    let attribution = context
+        .attribute_log(
+            &guild_id,
+            LogCategory::Channels,
+            &[
+                AuditLogEventType::ChannelUpdate,
+                AuditLogEventType::ChannelOverwriteCreate,
+                AuditLogEventType::ChannelOverwriteUpdate,
+                AuditLogEventType::ChannelOverwriteDelete,
+            ],
+            channel_id.cast(),
+        )
+        .await;
+    context.log(&guild_id, entry.attribution(attribution.as_ref())).await;
+}
@@ -1,9 +1,95 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use twilight_model::gateway::payload::incoming::GuildEmojisUpdate;
 
+use gearbot_2_lib::datastore::guild::LogCategory;
+use gearbot_2_lib::util::markers::{EmojiId, GuildId};
+use gearbot_2_lib::util::url::assemble_emoji_url;
+
+use crate::cache::{Emoji, Guild};
+use crate::logging::{name_and_id, role_names, LogEntry, COLOR_NEGATIVE, COLOR_NEUTRAL, COLOR_POSITIVE};
 use crate::util::bot_context::Context;
 
 pub fn on_emoji_update(emoji_update: GuildEmojisUpdate, context: &Context) {
     if let Some(guild) = context.cache.get_guild(&emoji_update.guild_id) {
-        guild.update_emoji(emoji_update.emojis)
+        let (old, new) = guild.update_emoji(emoji_update.emojis);
+        context.tasks.spawn(log_emoji_changes(
+            emoji_update.guild_id,
+            guild,
+            old,
+            new,
+            context.clone(),
+        ));
+    }
+}
+
+fn emoji_entry(
+    guild: &Guild,
+    emoji_id: &EmojiId,
+    emoji: &Emoji,
+    emoji_symbol: &'static str,
+    title: &str,
+    color: u32,
+) -> LogEntry {
+    let mut entry = LogEntry::new(LogCategory::Server, emoji_symbol, title, color)
+        .field("Emoji", name_and_id(&emoji.name, emoji_id))
+        .thumbnail(assemble_emoji_url(emoji_id, emoji.animated));
+    // emoji without roles are available to everyone
+    if !emoji.roles.is_empty() {
+        entry = entry.field("Restricted to", role_names(guild, &emoji.roles));
+    }
+    entry
+}
+
+async fn log_emoji_changes(
+    guild_id: GuildId,
+    guild: Arc<Guild>,
+    old: HashMap<EmojiId, Arc<Emoji>>,
+    new: HashMap<EmojiId, Arc<Emoji>>,
+    context: Context,
+) {
+    let mut entries = Vec::new();
+
+    for (emoji_id, emoji) in &new {
+        match old.get(emoji_id) {
+            None => entries.push(emoji_entry(
+                &guild,
+                emoji_id,
+                emoji,
+                "😀",
+                "Emoji added",
+                COLOR_POSITIVE,
+            )),
+            Some(old_emoji) => {
+                if old_emoji.name != emoji.name || old_emoji.roles != emoji.roles {
+                    let mut entry = emoji_entry(&guild, emoji_id, emoji, "📝", "Emoji updated", COLOR_NEUTRAL);
+                    if old_emoji.name != emoji.name {
+                        entry = entry.field("Renamed", format!("{} ➡ {}", old_emoji.name, emoji.name));
+                    }
+                    if old_emoji.roles != emoji.roles {
+                        entry = entry.field("Previously restricted to", role_names(&guild, &old_emoji.roles));
+                    }
+                    entries.push(entry);
+                }
+            }
+        }
+    }
+
+    for (emoji_id, emoji) in &old {
+        if !new.contains_key(emoji_id) {
+            entries.push(emoji_entry(
+                &guild,
+                emoji_id,
+                emoji,
+                "🗑️",
+                "Emoji removed",
+                COLOR_NEGATIVE,
+            ));
+        }
+    }
+
+    for entry in entries {
+        context.log(&guild_id, entry).await;
     }
 }
@@ -7,12 +7,15 @@ use twilight_model::gateway::payload::incoming::{GuildDelete, MemberChunk};
 use twilight_model::gateway::payload::outgoing::request_guild_members::RequestGuildMembersBuilder;
 use twilight_model::guild::{Guild as TwilightGuild, PartialGuild};
 
+use gearbot_2_lib::datastore::guild::LogCategory;
 use gearbot_2_lib::kafka::message::{General, Message};
 use gearbot_2_lib::kafka::sender::KafkaSender;
-use gearbot_2_lib::util::markers::GuildId;
+use gearbot_2_lib::util::markers::{GuildId, UserId};
+use gearbot_2_lib::util::url::{assemble_guild_banner_url, assemble_guild_icon_url, assemble_guild_splash_url};
 
 use crate::cache::guild::GuildCacheState;
 use crate::cache::{Guild, Member};
+use crate::logging::{maybe_name_and_id, LogEntry, COLOR_NEUTRAL};
 use crate::util::bot_context::Context;
 use crate::{communication, BotStatus};
 
@@ -28,13 +31,111 @@ pub fn on_guild_create(shard: u64, guild: TwilightGuild, context: &Context) {
 
 pub fn on_guild_update(guild: PartialGuild, context: &Context) {
     let id = guild.id;
-    if let (Some(_old), Some(_new)) = context.cache.update_guild(guild.id, guild) {
+    if let (Some(old), Some(new)) = context.cache.update_guild(guild.id, guild) {
         trace!("Updated a guild");
+        context.tasks.spawn(log_guild_update(id, old, new, context.clone()));
     } else {
         warn!("Received a guild update for a guild that wasn't cached: {}", id);
     }
 }
 
+async fn log_guild_update(guild_id: GuildId, old: Arc<Guild>, new: Arc<Guild>, context: Context) {
+    let mut entry = LogEntry::new(LogCategory::Server, "⚙️", "Server updated", COLOR_NEUTRAL);
+    let mut changed = false;
+
+    let describe_text = |text: &Option<String>| text.as_deref().unwrap_or("*None*").to_string();
+
+    if old.name != new.name {
+        entry = entry.line(format!("**Name**: {} ➡ {}", old.name, new.name));
+        changed = true;
+    }
+    if old.icon != new.icon {
+        entry = match &new.icon {
+            Some(icon) => entry
+                .line("**Icon**: updated")
+                .thumbnail(assemble_guild_icon_url(&guild_id, icon)),
+            None => entry.line("**Icon**: removed"),
+        };
+        changed = true;
+    }
+    if old.banner != new.banner {
+        entry = match &new.banner {
+            Some(banner) => entry
+                .line("**Banner**: updated")
+                .image(assemble_guild_banner_url(&guild_id, banner)),
+            None => entry.line("**Banner**: removed"),
+        };
+        changed = true;
+    }
+    if old.splash != new.splash {
+        entry = match &new.splash {
+            Some(splash) => entry.line(format!(
+                "**Invite splash**: updated (<{}>)",
+                assemble_guild_splash_url(&guild_id, splash)
+            )),
+            None => entry.line("**Invite splash**: removed"),
+        };
+        changed = true;
+    }
+    if old.owner != new.owner {
+        let describe_user = |user_id: &UserId| maybe_name_and_id(context.cache.get_user(user_id), user_id);
+        entry = entry
+            .line(format!(
+                "**Owner**: {} ➡ {}",
+                describe_user(&old.owner),
+                describe_user(&new.owner)
+            ))
+            .alert("Server ownership was transferred");
+        changed = true;
+    }
+    if old.verification_level != new.verification_level {
+        entry = entry.line(format!(
+            "**Verification level**: {:?} ➡ {:?}",
+            old.verification_level, new.verification_level
+        ));
+        if (new.verification_level as u8) < (old.verification_level as u8) {
+            entry = entry.alert("Verification level was lowered");
+        }
+        changed = true;
+    }
+    if old.mfa != new.mfa {
+        entry = entry.line(format!(
+            "**2FA requirement for moderators**: {:?} ➡ {:?}",
+            old.mfa, new.mfa
+        ));
+        changed = true;
+    }
+    if old.nsfw != new.nsfw {
+        entry = entry.line(format!("**NSFW level**: {:?} ➡ {:?}", old.nsfw, new.nsfw));
+        changed = true;
+    }
+    if old.vanity_invite != new.vanity_invite {
+        entry = entry.line(format!(
+            "**Vanity invite**: {} ➡ {}",
+            describe_text(&old.vanity_invite),
+            describe_text(&new.vanity_invite)
+        ));
+        changed = true;
+    }
+    if old.description != new.description {
+        entry = entry.line(format!(
+            "**Description**: {} ➡ {}",
+            describe_text(&old.description),
+            describe_text(&new.description)
+        ));
+        changed = true;
+    }
+    if old.guild_locale != new.guild_locale {
+        entry = entry.line(format!("**Locale**: {} ➡ {}", old.guild_locale, new.guild_locale));
+        changed = true;
+    }
+
+    // features, limits and the like are not logged
+    if changed {
+        context.log(&guild_id, entry).await;
+    }
+}
+
 pub fn on_guild_delete(shard: u64, event: GuildDelete, context: &Context) {
     let old = context
         .cache
@@ -126,6 +227,17 @@ async fn new_guild_processor(shard: u64, guild_id: GuildId, _guild: Arc<Guild>,
         request_guild_members(shard, guild_id, &context).await
     }
 
+    // invites are only needed to show what invite was used in the join logs
+    if context.logs_category(&guild_id, LogCategory::Members).await {
+        if let Err(e) = context.track_invites(&guild_id).await {
+            warn!(
+                "Failed to load the invites for guild {}: {}",
+                guild_id,
+                e.get_log_error()
+            );
+        }
+    }
+
     //todo: actually process the new guild
 }
 
@@ -0,0 +1,16 @@
+use twilight_model::gateway::payload::incoming::{InviteCreate, InviteDelete};
+
+use crate::cache::Invite;
+use crate::util::bot_context::Context;
+
+pub fn on_invite_create(invite: &InviteCreate, context: &Context) {
+    if let Some(guild) = context.cache.get_guild(&invite.guild_id) {
+        guild.insert_invite(Invite::from_create(invite));
+    }
+}
+
+pub fn on_invite_delete(invite: &InviteDelete, context: &Context) {
+    if let Some(guild) = context.cache.get_guild(&invite.guild_id) {
+        guild.remove_invite(&invite.code);
+    }
+}
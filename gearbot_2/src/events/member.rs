@@ -1,19 +1,36 @@
 use std::sync::Arc;
 
+use chrono::{Duration, Utc};
 use tracing::{trace, warn};
 use twilight_model::gateway::payload::incoming::{MemberRemove, MemberUpdate};
+use twilight_model::guild::audit_log::AuditLogEventType;
 use twilight_model::guild::Member as TwilightMember;
 
-use gearbot_2_lib::util::markers::{GuildId, UserId};
+use gearbot_2_lib::datastore::guild::{GuildDatastore, InfractionType, LogCategory};
+use gearbot_2_lib::util::markers::{GuildId, RoleId, UserId};
+use gearbot_2_lib::util::url::{assemble_guild_avatar_url, assemble_user_avatar};
+use gearbot_2_lib::util::{snowflake_age, snowflake_timestamp, timestamp_age};
 
 use crate::cache::guild::GuildCacheState;
-use crate::cache::{Member, User};
+use crate::cache::invite::UsedInvite;
+use crate::cache::{Guild, Member, User};
+use crate::events::async_wrapper;
+use crate::logging::audit::Attribution;
+use crate::logging::{
+    maybe_name_and_id, name_and_id, role_names, LogEntry, COLOR_NEGATIVE, COLOR_NEUTRAL, COLOR_POSITIVE, LOG_LANG,
+};
+use crate::moderation::auto_roles::assign_roles;
+use crate::moderation::escalation::record_infraction;
+use crate::moderation::names::check_name;
+use crate::moderation::raid::check_join;
+use crate::moderation::sticky_roles::{restore_member, store_member};
 use crate::util::bot_context::Context;
 
 pub fn on_member_add(member: TwilightMember, context: &Context) {
     let user_id = member.user.id;
-    trace!("Member {} joined {}", &user_id, &member.guild_id);
-    if let Some(guild) = context.cache.get_guild(&member.guild_id) {
+    let guild_id = member.guild_id;
+    trace!("Member {} joined {}", &user_id, &guild_id);
+    if let Some(guild) = context.cache.get_guild(&guild_id) {
         let member = if let Some(user) = context.cache.get_user(&user_id) {
             Member::assemble(member, user)
         } else {
@@ -28,10 +45,95 @@ pub fn on_member_add(member: TwilightMember, context: &Context) {
         // in either case we need to add this as mutual guild
         member.add_mutual_guild();
 
-        guild.insert_member(user_id, Arc::new(member));
-        context.metrics.members.inc()
+        let member = Arc::new(member);
+        guild.insert_member(user_id, member.clone());
+        context.metrics.members.inc();
+
+        context
+            .tasks
+            .spawn(check_join(guild_id, user_id, member.user(), context.clone()));
+        context.tasks.spawn(check_name(guild_id, user_id, context.clone()));
+        async_wrapper(
+            restore_member(guild_id, user_id, context.clone()),
+            "sticky_roles_restore",
+            context,
+        );
+        async_wrapper(
+            assign_roles(guild_id, user_id, false, context.clone()),
+            "auto_roles_join",
+            context,
+        );
+        context
+            .tasks
+            .spawn(log_member_join(guild_id, user_id, member, context.clone()));
     } else {
-        warn!("Got a member add event for an uncached guild: {}", member.guild_id);
+        warn!("Got a member add event for an uncached guild: {}", guild_id);
+    }
+}
+
+async fn log_member_join(guild_id: GuildId, user_id: UserId, member: Arc<Member>, context: Context) {
+    let user = member.user();
+    let mut entry = LogEntry::new(LogCategory::Members, "📥", "Member joined", COLOR_POSITIVE)
+        .user(user_id, user.bot, &member.roles)
+        .field("User", name_and_id(&user, user_id))
+        .field("Account age", snowflake_age(&user_id, 2, LOG_LANG, &context.translator))
+        .thumbnail(assemble_user_avatar(&user_id, user.discriminator, user.avatar.as_ref()));
+
+    // flag accounts that are younger then the guild is comfortable with
+    if let Ok(info) = context.get_guild_info(&guild_id).await {
+        let threshold = Duration::hours(info.config.logging.new_account_threshold_hours as i64);
+        if Utc::now().signed_duration_since(snowflake_timestamp(&user_id)) < threshold {
+            entry = entry.alert("New account");
+        }
+
+        match context.cache.get_guild(&guild_id) {
+            Some(guild) if guild.is_tracking_invites() => match context.find_used_invite(&guild_id).await {
+                Ok(Some(invite)) => {
+                    entry = entry.field("Invite", describe_invite(&invite, &context));
+                    let datastore = GuildDatastore::new(&context.datastore, &info.encryption_key, &guild_id);
+                    if let Err(e) = datastore
+                        .store_invite_use(&invite.code, invite.inviter.as_ref(), &user_id)
+                        .await
+                    {
+                        warn!("Failed to store the invite used to join guild {}: {}", guild_id, e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!(
+                    "Failed to figure out the invite used to join guild {}: {}",
+                    guild_id,
+                    e.get_log_error()
+                ),
+            },
+            // logs got turned on since this guild was cached, start tracking for the next joins
+            Some(_) if context.logs_category(&guild_id, LogCategory::Members).await => {
+                if let Err(e) = context.track_invites(&guild_id).await {
+                    warn!(
+                        "Failed to load the invites for guild {}: {}",
+                        guild_id,
+                        e.get_log_error()
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    context.log(&guild_id, entry).await;
+}
+
+fn describe_invite(invite: &UsedInvite, context: &Context) -> String {
+    if invite.vanity {
+        return format!("`{}` (vanity url)", invite.code);
+    }
+
+    match &invite.inviter {
+        Some(inviter) => format!(
+            "`{}` by {}",
+            invite.code,
+            maybe_name_and_id(context.cache.get_user(inviter), inviter)
+        ),
+        None => format!("`{}`", invite.code),
     }
 }
 
@@ -59,8 +161,17 @@ pub fn on_member_update(member_update: MemberUpdate, context: &Context) {
                         Arc::new(Member::convert_update(member_update, Some(old_user)))
                     };
                     guild.insert_member(user_id, new_member.clone());
-                    let _new_user = new_member.user();
-                    tokio::spawn(log_updated_member(
+                    if old_member.nickname != new_member.nickname {
+                        context.tasks.spawn(check_name(guild_id, user_id, context.clone()));
+                    }
+                    if old_member.pending && !new_member.pending {
+                        async_wrapper(
+                            assign_roles(guild_id, user_id, true, context.clone()),
+                            "auto_roles_screening",
+                            context,
+                        );
+                    }
+                    context.tasks.spawn(log_updated_member(
                         user_id,
                         guild_id,
                         old_member,
@@ -104,11 +215,15 @@ fn handle_updated_user(user_id: UserId, old_user: Arc<User>, new_user: &Arc<User
     context.cache.for_each_guild(|guild_id, guild| {
         if let Some(member) = guild.get_member(&user_id) {
             member.set_user(new_user.clone());
+            // a nickname hides the username, no need to look at it then
+            if old_user.name != new_user.name && member.nickname.is_none() {
+                context.tasks.spawn(check_name(*guild_id, user_id, context.clone()));
+            }
             guild_list.push(*guild_id)
         }
     });
 
-    tokio::spawn(log_updated_user(
+    context.tasks.spawn(log_updated_user(
         user_id,
         old_user,
         new_user.clone(),
@@ -118,21 +233,164 @@ fn handle_updated_user(user_id: UserId, old_user: Arc<User>, new_user: &Arc<User
 }
 
 async fn log_updated_user(
-    _user_id: UserId,
-    _old_user: Arc<User>,
-    _new_user: Arc<User>,
-    _guild_list: Vec<GuildId>,
-    _context: Context,
+    user_id: UserId,
+    old_user: Arc<User>,
+    new_user: Arc<User>,
+    guild_list: Vec<GuildId>,
+    context: Context,
 ) {
+    let name_changed = old_user.name != new_user.name || old_user.discriminator != new_user.discriminator;
+    let avatar_changed = old_user.avatar != new_user.avatar;
+
+    // flag changes are not logged
+    if !name_changed && !avatar_changed {
+        return;
+    }
+
+    let old_avatar = assemble_user_avatar(&user_id, old_user.discriminator, old_user.avatar.as_ref());
+    let new_avatar = assemble_user_avatar(&user_id, new_user.discriminator, new_user.avatar.as_ref());
+
+    for guild_id in guild_list {
+        let roles = context
+            .cache
+            .get_guild_member(&guild_id, &user_id)
+            .map(|member| member.roles.clone())
+            .unwrap_or_default();
+        let mut entry = LogEntry::new(LogCategory::Members, "🪪", "User updated", COLOR_NEUTRAL)
+            .user(user_id, new_user.bot, &roles)
+            .field("User", name_and_id(&new_user, user_id));
+        if name_changed {
+            entry = entry
+                .field("Old name", old_user.to_string())
+                .field("New name", new_user.to_string());
+        }
+        if avatar_changed {
+            entry = entry
+                .field("Old avatar", format!("<{}>", old_avatar))
+                .field("New avatar", format!("<{}>", new_avatar));
+        }
+        entry = entry.thumbnail(old_avatar.clone()).image(new_avatar.clone());
+
+        context.log(&guild_id, entry).await;
+    }
 }
 
 async fn log_updated_member(
-    _user_id: UserId,
-    _guild_id: GuildId,
-    _old_member: Arc<Member>,
-    _new_member: Arc<Member>,
-    _context: Context,
+    user_id: UserId,
+    guild_id: GuildId,
+    old_member: Arc<Member>,
+    new_member: Arc<Member>,
+    context: Context,
 ) {
+    let user = new_member.user();
+    let user_line = name_and_id(&user, user_id);
+    let member_entry = |emoji, title: &str, color| {
+        LogEntry::new(LogCategory::Members, emoji, title, color)
+            .user(user_id, user.bot, &new_member.roles)
+            .field("User", &user_line)
+    };
+
+    if old_member.nickname != new_member.nickname {
+        let entry = member_entry("🏷️", "Nickname changed", COLOR_NEUTRAL)
+            .field("Before", old_member.nickname.as_deref().unwrap_or("*None*"))
+            .field("After", new_member.nickname.as_deref().unwrap_or("*None*"));
+        // people changing their own nickname show up in the audit log as well
+        let attribution = context
+            .attribute_log(
+                &guild_id,
+                LogCategory::Members,
+                &[AuditLogEventType::MemberUpdate],
+                user_id.cast(),
+            )
+            .await;
+        context.log(&guild_id, entry.attribution(attribution.as_ref())).await;
+    }
+
+    if old_member.roles != new_member.roles {
+        let added = new_member
+            .roles
+            .iter()
+            .filter(|role| !old_member.roles.contains(role))
+            .copied()
+            .collect::<Vec<RoleId>>();
+        let removed = old_member
+            .roles
+            .iter()
+            .filter(|role| !new_member.roles.contains(role))
+            .copied()
+            .collect::<Vec<RoleId>>();
+
+        // skip updates where only the order changed
+        if !added.is_empty() || !removed.is_empty() {
+            if let Some(guild) = context.cache.get_guild(&guild_id) {
+                let mut entry = member_entry("🎭", "Roles changed", COLOR_NEUTRAL);
+                if !added.is_empty() {
+                    entry = entry.field("Added", role_names(&guild, &added));
+                }
+                if !removed.is_empty() {
+                    entry = entry.field("Removed", role_names(&guild, &removed));
+                }
+                let attribution = context
+                    .attribute_log(
+                        &guild_id,
+                        LogCategory::Members,
+                        &[AuditLogEventType::MemberRoleUpdate],
+                        user_id.cast(),
+                    )
+                    .await;
+                context.log(&guild_id, entry.attribution(attribution.as_ref())).await;
+            }
+        }
+    }
+
+    if old_member.avatar != new_member.avatar {
+        let entry = member_entry("🖼️", "Server avatar changed", COLOR_NEUTRAL);
+        let entry = match &new_member.avatar {
+            Some(avatar) => entry.thumbnail(assemble_guild_avatar_url(&guild_id, &user_id, avatar)),
+            None => entry.line("Server avatar removed"),
+        };
+        context.log(&guild_id, entry).await;
+    }
+
+    if old_member.communication_disabled_until != new_member.communication_disabled_until {
+        // timeouts expire on their own without an update, so check if the old one was still active
+        let now = Utc::now().timestamp();
+        let (entry, applied) = match new_member.communication_disabled_until {
+            Some(until) if until.as_secs() > now => (
+                member_entry("🔇", "Timeout applied", COLOR_NEGATIVE)
+                    .field("Until", format!("<t:{}:f>", until.as_secs())),
+                true,
+            ),
+            _ => match old_member.communication_disabled_until {
+                Some(until) if until.as_secs() > now => (
+                    member_entry("🔊", "Timeout lifted", COLOR_POSITIVE)
+                        .field("Was until", format!("<t:{}:f>", until.as_secs())),
+                    false,
+                ),
+                _ => return,
+            },
+        };
+        let attribution = context
+            .attribute_moderation(
+                &guild_id,
+                LogCategory::Members,
+                &[AuditLogEventType::MemberUpdate],
+                user_id.cast(),
+            )
+            .await;
+        context.log(&guild_id, entry.attribution(attribution.as_ref())).await;
+
+        if applied {
+            record_manual_infraction(
+                &guild_id,
+                &user_id,
+                attribution.as_ref(),
+                InfractionType::Timeout,
+                &context,
+            )
+            .await;
+        }
+    }
 }
 
 pub fn on_member_remove(member_remove: MemberRemove, context: &Context) {
@@ -140,7 +398,18 @@ pub fn on_member_remove(member_remove: MemberRemove, context: &Context) {
 
     if let Some(guild) = context.cache.get_guild(&member_remove.guild_id) {
         let old = guild.remove_member(&member_remove.user.id);
-        if let Some(old) = old {
+        if let Some(old) = &old {
+            async_wrapper(
+                store_member(
+                    member_remove.guild_id,
+                    member_remove.user.id,
+                    old.clone(),
+                    context.clone(),
+                ),
+                "sticky_roles_store",
+                context,
+            );
+
             // cleanup the user if this was the last mutual guild
             // we still have an arc to use but this purges the cached cache copy if needed
             if old.get_mutual_guilds() == 0 {
@@ -152,7 +421,15 @@ pub fn on_member_remove(member_remove: MemberRemove, context: &Context) {
                 &member_remove.user.id, &member_remove.guild_id
             );
         }
-        //TODO: handle
+
+        context.tasks.spawn(log_member_leave(
+            member_remove.guild_id,
+            member_remove.user.id,
+            Arc::new(User::assemble(member_remove.user, None)),
+            old,
+            guild,
+            context.clone(),
+        ));
     } else {
         warn!(
             "Got a member remove event for an uncached guild: {}",
@@ -160,3 +437,82 @@ pub fn on_member_remove(member_remove: MemberRemove, context: &Context) {
         );
     }
 }
+
+async fn log_member_leave(
+    guild_id: GuildId,
+    user_id: UserId,
+    user: Arc<User>,
+    old_member: Option<Arc<Member>>,
+    guild: Arc<Guild>,
+    context: Context,
+) {
+    let roles = old_member
+        .as_ref()
+        .map(|member| member.roles.clone())
+        .unwrap_or_default();
+    // leaving and getting kicked look the same on the gateway
+    let kick = context
+        .attribute_moderation(
+            &guild_id,
+            LogCategory::Members,
+            &[AuditLogEventType::MemberKick],
+            user_id.cast(),
+        )
+        .await;
+    let mut entry = match &kick {
+        Some(_) => LogEntry::new(LogCategory::Members, "👢", "Member kicked", COLOR_NEGATIVE),
+        None => LogEntry::new(LogCategory::Members, "📤", "Member left", COLOR_NEGATIVE),
+    }
+    .user(user_id, user.bot, &roles)
+    .field("User", name_and_id(&user, user_id))
+    .field("Account age", snowflake_age(&user_id, 2, LOG_LANG, &context.translator))
+    .thumbnail(assemble_user_avatar(&user_id, user.discriminator, user.avatar.as_ref()));
+
+    // without a cached member we don't know how long they where here or what roles they had
+    if let Some(member) = old_member {
+        entry = entry
+            .field(
+                "Time in server",
+                timestamp_age(&member.joined_at, 2, LOG_LANG, &context.translator),
+            )
+            .field("Roles", role_names(&guild, &member.roles));
+    }
+
+    context.log(&guild_id, entry.attribution(kick.as_ref())).await;
+
+    record_manual_infraction(&guild_id, &user_id, kick.as_ref(), InfractionType::Kick, &context).await;
+}
+
+/// Add punishments done by hand to the moderation history.
+/// The ones we did ourselves are already in there, and without attribution we don't know if it was a punishment at all
+async fn record_manual_infraction(
+    guild_id: &GuildId,
+    user_id: &UserId,
+    attribution: Option<&Attribution>,
+    kind: InfractionType,
+    context: &Context,
+) {
+    let attribution = match attribution {
+        Some(attribution) if attribution.moderator != context.bot_id.cast() => attribution,
+        _ => return,
+    };
+
+    if let Err(e) = record_infraction(
+        guild_id,
+        user_id,
+        Some(&attribution.moderator),
+        kind,
+        attribution.reason.as_deref(),
+        &[],
+        context,
+    )
+    .await
+    {
+        warn!(
+            "Failed to record a {:?} infraction in guild {}: {}",
+            kind,
+            guild_id,
+            e.get_log_error()
+        );
+    }
+}
@@ -4,12 +4,53 @@ use twilight_model::gateway::payload::incoming::{MessageCreate, MessageUpdate};
 use gearbot_2_lib::datastore::guild::GuildDatastore;
 use gearbot_2_lib::util::GearResult;
 
+use crate::moderation::censor::censor_message;
+use crate::moderation::{anti_spam, phishing};
 use crate::util::bot_context::Context;
 
 pub async fn on_message(message: MessageCreate, context: Context) -> GearResult<()> {
     // we don't care about dms
     if let Some(guild_id) = &message.guild_id {
         let info = context.get_guild_info(guild_id).await?;
+        let roles = message
+            .member
+            .as_ref()
+            .map_or_else(Vec::new, |member| member.roles.clone());
+
+        // scams are dealt with harder so check those first, no need to censor a message that's already gone
+        let mut removed = false;
+        if info.config.phishing.enabled {
+            removed = phishing::check_message(
+                *guild_id,
+                message.channel_id,
+                message.id,
+                &message.author,
+                &roles,
+                &message.content,
+                &message.embeds,
+                &info.config.phishing,
+                &context,
+            )
+            .await;
+        }
+
+        if info.config.censoring.enabled && !removed {
+            censor_message(
+                *guild_id,
+                message.channel_id,
+                message.id,
+                &message.author,
+                &roles,
+                &message.content,
+                &info.config.censoring,
+                &context,
+            )
+            .await;
+        }
+
+        if info.config.anti_spam.enabled {
+            anti_spam::check_message(*guild_id, &message, &info.config.anti_spam, &context).await;
+        }
 
         // do we want messages logged for this guild?
         if !info.config.message_logs.enabled {
@@ -40,6 +81,46 @@ pub async fn on_message_update(update: MessageUpdate, context: Context) -> GearR
     if let Some(guild_id) = &update.guild_id {
         let info = context.get_guild_info(guild_id).await?;
 
+        // edits can sneak in content that would have been caught on create, and link embeds only show up in an update
+        if let Some(author) = &update.author {
+            let roles = context
+                .cache
+                .get_guild(guild_id)
+                .and_then(|guild| guild.get_member(&author.id))
+                .map_or_else(Vec::new, |member| member.roles.clone());
+            let content = update.content.as_deref().unwrap_or_default();
+
+            let mut removed = false;
+            if info.config.phishing.enabled {
+                removed = phishing::check_message(
+                    *guild_id,
+                    update.channel_id,
+                    update.id,
+                    author,
+                    &roles,
+                    content,
+                    update.embeds.as_deref().unwrap_or_default(),
+                    &info.config.phishing,
+                    &context,
+                )
+                .await;
+            }
+
+            if info.config.censoring.enabled && !removed && update.content.is_some() {
+                censor_message(
+                    *guild_id,
+                    update.channel_id,
+                    update.id,
+                    author,
+                    &roles,
+                    content,
+                    &info.config.censoring,
+                    &context,
+                )
+                .await;
+            }
+        }
+
         // do we want messages logged for this guild?
         if !info.config.message_logs.enabled {
             return Ok(());
@@ -6,9 +6,11 @@ use twilight_model::gateway::event::Event;
 use gearbot_2_lib::util::GearResult;
 pub use other::on_ready;
 
+use crate::events::ban::{on_ban_add, on_ban_remove};
 use crate::events::channel::{on_channel_create, on_channel_delete, on_channel_update};
 use crate::events::emoji::on_emoji_update;
 use crate::events::guild::{on_guild_create, on_guild_delete, on_guild_update, on_member_chunk};
+use crate::events::invite::{on_invite_create, on_invite_delete};
 use crate::events::member::{on_member_add, on_member_remove, on_member_update};
 use crate::events::message::{on_message, on_message_update};
 use crate::events::other::on_resume;
@@ -19,9 +21,11 @@ use crate::events::thread::{
 use crate::events::voice::on_voice_state_update;
 use crate::util::bot_context::Context;
 
+mod ban;
 mod channel;
 mod emoji;
 mod guild;
+mod invite;
 mod member;
 mod message;
 mod other;
@@ -32,6 +36,8 @@ mod voice;
 //Just a hub function to fan out to the relevant handlers
 pub fn handle_gateway_event(shard: u64, event: Event, context: &Context) {
     match event {
+        Event::BanAdd(ban_add) => on_ban_add(ban_add, context),
+        Event::BanRemove(ban_remove) => on_ban_remove(ban_remove, context),
         Event::ChannelCreate(create) => on_channel_create(create.0, context),
         Event::ChannelDelete(delete) => on_channel_delete(delete.0, context),
         Event::ChannelUpdate(update) => on_channel_update(update.0, context),
@@ -39,6 +45,8 @@ pub fn handle_gateway_event(shard: u64, event: Event, context: &Context) {
         Event::GuildDelete(guild_delete) => on_guild_delete(shard, *guild_delete, context),
         Event::GuildEmojisUpdate(emoji_update) => on_emoji_update(emoji_update, context),
         Event::GuildUpdate(guild_update) => on_guild_update(guild_update.0, context),
+        Event::InviteCreate(invite_create) => on_invite_create(&invite_create, context),
+        Event::InviteDelete(invite_delete) => on_invite_delete(&invite_delete, context),
         Event::MemberAdd(member_add) => on_member_add(member_add.0, context),
         Event::MemberRemove(member_remove) => on_member_remove(member_remove, context),
         Event::MemberUpdate(member_update) => on_member_update(*member_update, context),
@@ -59,17 +67,19 @@ pub fn handle_gateway_event(shard: u64, event: Event, context: &Context) {
 
         // these ones don't do anything cache related and are async only so we might as well spawn them here
         Event::MessageCreate(message_create) => {
-            async_wrapper(on_message(*message_create, context.clone()), "message_create")
-        }
-        Event::MessageUpdate(message_update) => {
-            async_wrapper(on_message_update(*message_update, context.clone()), "message_update")
+            async_wrapper(on_message(*message_create, context.clone()), "message_create", context)
         }
+        Event::MessageUpdate(message_update) => async_wrapper(
+            on_message_update(*message_update, context.clone()),
+            "message_update",
+            context,
+        ),
         _ => {}
     }
 }
 
-fn async_wrapper(todo: impl Future<Output = GearResult<()>> + Send + 'static, name: &'static str) {
-    tokio::spawn(async move {
+fn async_wrapper(todo: impl Future<Output = GearResult<()>> + Send + 'static, name: &'static str, context: &Context) {
+    context.tasks.spawn(async move {
         if let Err(e) = todo.await {
             if e.is_user_error() {
                 error!(
@@ -1,19 +1,159 @@
 use std::sync::Arc;
+
 use twilight_model::gateway::payload::incoming::{RoleCreate, RoleDelete, RoleUpdate};
+use twilight_model::guild::audit_log::AuditLogEventType;
+
+use gearbot_2_lib::datastore::guild::LogCategory;
+use gearbot_2_lib::util::markers::GuildId;
+use gearbot_2_lib::util::url::assemble_role_icon_url;
 
 use crate::cache::Role;
+use crate::logging::permissions::{dangerous_permissions, permission_list};
+use crate::logging::{name_and_id, LogEntry, COLOR_NEGATIVE, COLOR_NEUTRAL, COLOR_POSITIVE};
 use crate::util::bot_context::Context;
 
 pub fn on_role_create(role_create: RoleCreate, context: &Context) {
     let new: Arc<Role> = Arc::new(Role::from_role(role_create.role));
-    context.cache.insert_role(&role_create.guild_id, new);
+    context.cache.insert_role(&role_create.guild_id, new.clone());
+    context
+        .tasks
+        .spawn(log_role_create(role_create.guild_id, new, context.clone()));
 }
 
 pub fn on_role_update(role_update: RoleUpdate, context: &Context) {
     let new: Arc<Role> = Arc::new(Role::from_role(role_update.role));
-    let _old = context.cache.insert_role(&role_update.guild_id, new);
+    if let Some(old) = context.cache.insert_role(&role_update.guild_id, new.clone()) {
+        context
+            .tasks
+            .spawn(log_role_update(role_update.guild_id, old, new, context.clone()));
+    }
 }
 
 pub fn on_role_delete(role_delete: RoleDelete, context: &Context) {
-    let _old = context.cache.remove_role(&role_delete.guild_id, &role_delete.role_id);
+    if let Some(old) = context.cache.remove_role(&role_delete.guild_id, &role_delete.role_id) {
+        context
+            .tasks
+            .spawn(log_role_delete(role_delete.guild_id, old, context.clone()));
+    }
+}
+
+fn role_line(role: &Role) -> String {
+    name_and_id(&role.name, role.id)
+}
+
+async fn log_role_create(guild_id: GuildId, role: Arc<Role>, context: Context) {
+    let mut entry = LogEntry::new(LogCategory::Roles, "🆕", "Role created", COLOR_POSITIVE)
+        .field("Role", role_line(&role))
+        .field("Permissions", permission_list(role.permissions));
+
+    let dangerous = role.permissions & dangerous_permissions();
+    if !dangerous.is_empty() {
+        entry = entry.alert(format!(
+            "Created with dangerous permissions: {}",
+            permission_list(dangerous)
+        ));
+    }
+
+    let attribution = context
+        .attribute_log(
+            &guild_id,
+            LogCategory::Roles,
+            &[AuditLogEventType::RoleCreate],
+            role.id.cast(),
+        )
+        .await;
+    context.log(&guild_id, entry.attribution(attribution.as_ref())).await;
+}
+
+async fn log_role_delete(guild_id: GuildId, role: Arc<Role>, context: Context) {
+    let attribution = context
+        .attribute_log(
+            &guild_id,
+            LogCategory::Roles,
+            &[AuditLogEventType::RoleDelete],
+            role.id.cast(),
+        )
+        .await;
+    let entry = LogEntry::new(LogCategory::Roles, "🗑️", "Role deleted", COLOR_NEGATIVE)
+        .field("Role", role_line(&role))
+        .field("Permissions", permission_list(role.permissions))
+        .attribution(attribution.as_ref());
+
+    context.log(&guild_id, entry).await;
+}
+
+async fn log_role_update(guild_id: GuildId, old: Arc<Role>, new: Arc<Role>, context: Context) {
+    let mut entry =
+        LogEntry::new(LogCategory::Roles, "📝", "Role updated", COLOR_NEUTRAL).field("Role", role_line(&new));
+    let mut changed = false;
+
+    if old.name != new.name {
+        entry = entry.line(format!("**Name**: {} ➡ {}", old.name, new.name));
+        changed = true;
+    }
+    if old.color != new.color {
+        entry = entry.line(format!("**Colour**: #{:06X} ➡ #{:06X}", old.color, new.color));
+        changed = true;
+    }
+    if old.hoisted != new.hoisted {
+        entry = entry.line(format!("**Displayed separately**: {} ➡ {}", old.hoisted, new.hoisted));
+        changed = true;
+    }
+    if old.icon != new.icon {
+        entry = match &new.icon {
+            Some(icon) => entry
+                .line("**Icon**: updated")
+                .thumbnail(assemble_role_icon_url(&new.id, icon)),
+            None => entry.line("**Icon**: removed"),
+        };
+        changed = true;
+    }
+    if old.emoji != new.emoji {
+        entry = entry.line(format!(
+            "**Emoji**: {} ➡ {}",
+            old.emoji.as_deref().unwrap_or("*None*"),
+            new.emoji.as_deref().unwrap_or("*None*")
+        ));
+        changed = true;
+    }
+    let moved = old.position != new.position;
+    if moved {
+        entry = entry.line(format!("**Position**: {} ➡ {}", old.position, new.position));
+    }
+    if old.permissions != new.permissions {
+        let granted = new.permissions - old.permissions;
+        let revoked = old.permissions - new.permissions;
+        if !granted.is_empty() {
+            entry = entry.field("Permissions granted", permission_list(granted));
+        }
+        if !revoked.is_empty() {
+            entry = entry.field("Permissions revoked", permission_list(revoked));
+        }
+
+        let dangerous = granted & dangerous_permissions();
+        if !dangerous.is_empty() {
+            entry = entry.alert(format!("Dangerous permissions granted: {}", permission_list(dangerous)));
+        }
+        changed = true;
+    }
+
+    if !changed && !moved {
+        return;
+    }
+
+    let attribution = context
+        .attribute_log(
+            &guild_id,
+            LogCategory::Roles,
+            &[AuditLogEventType::RoleUpdate],
+            new.id.cast(),
+        )
+        .await;
+    // moving one role shifts the position of every role in between, each with their own update.
+    // Only the role that was actually moved shows up in the audit log, the rest are left out so
+    // a single reorder doesn't flood the logs
+    if !changed && attribution.is_none() {
+        return;
+    }
+    context.log(&guild_id, entry.attribution(attribution.as_ref())).await;
 }
@@ -1,20 +1,48 @@
+use std::sync::Arc;
+
 use tracing::warn;
 use twilight_model::channel::Channel as TwilightChannel;
+use twilight_model::channel::ChannelType;
 use twilight_model::gateway::payload::incoming::{ThreadDelete, ThreadListSync, ThreadMembersUpdate};
 
+use gearbot_2_lib::datastore::guild::LogCategory;
+use gearbot_2_lib::util::markers::{ChannelId, GuildId, UserId};
+
+use crate::cache::Channel;
 use crate::events::channel::{cache_channel_create, cache_channel_delete, cache_channel_update};
+use crate::logging::{
+    channel_name, channel_type_name, maybe_name_and_id, name_and_id, LogEntry, COLOR_NEGATIVE, COLOR_NEUTRAL,
+    COLOR_POSITIVE,
+};
 use crate::util::bot_context::Context;
 
 pub fn on_thread_create(channel: TwilightChannel, context: &Context) {
-    if let Some(_new) = cache_channel_create(channel, context) {}
+    let thread_id = channel.id();
+    if let Some((guild_id, new)) = cache_channel_create(channel, context) {
+        context
+            .tasks
+            .spawn(log_thread_create(guild_id, thread_id, new, context.clone()));
+    }
 }
 
 pub fn on_thread_delete(thread_delete: ThreadDelete, context: &Context) {
-    if let Some(_old) = cache_channel_delete(&thread_delete.guild_id, &thread_delete.id, context) {}
+    if let Some(old) = cache_channel_delete(&thread_delete.guild_id, &thread_delete.id, context) {
+        context.tasks.spawn(log_thread_delete(
+            thread_delete.guild_id,
+            thread_delete.id,
+            old,
+            context.clone(),
+        ));
+    }
 }
 
 pub fn on_thread_update(channel: TwilightChannel, context: &Context) {
-    if let Some((_old, _new)) = cache_channel_update(channel, context) {}
+    let thread_id = channel.id();
+    if let Some((guild_id, Some(old), new)) = cache_channel_update(channel, context) {
+        context
+            .tasks
+            .spawn(log_thread_update(guild_id, thread_id, old, new, context.clone()));
+    }
 }
 
 pub fn on_thread_sync(sync: ThreadListSync, context: &Context) {
@@ -25,4 +53,145 @@ pub fn on_thread_sync(sync: ThreadListSync, context: &Context) {
     }
 }
 
-pub fn on_thread_members_update(_update: ThreadMembersUpdate, _context: &Context) {}
+pub fn on_thread_members_update(update: ThreadMembersUpdate, context: &Context) {
+    if update.added_members.is_empty() && update.removed_member_ids.is_empty() {
+        return;
+    }
+
+    let added = update
+        .added_members
+        .iter()
+        .filter_map(|member| member.user_id)
+        .collect::<Vec<UserId>>();
+    context.tasks.spawn(log_thread_members(
+        update.guild_id,
+        update.id,
+        added,
+        update.removed_member_ids,
+        context.clone(),
+    ));
+}
+
+fn thread_line(thread_id: &ChannelId, thread: &Channel) -> String {
+    name_and_id(&thread.name, thread_id)
+}
+
+// filters match on the parent so excluding a channel also excludes its threads
+fn filter_channel(thread_id: &ChannelId, thread: &Channel) -> ChannelId {
+    thread.parent_id.unwrap_or(*thread_id)
+}
+
+async fn log_thread_create(guild_id: GuildId, thread_id: ChannelId, thread: Arc<Channel>, context: Context) {
+    let mut entry = LogEntry::new(LogCategory::Channels, "🧵", "Thread created", COLOR_POSITIVE)
+        .channel(filter_channel(&thread_id, &thread))
+        .field("Thread", thread_line(&thread_id, &thread))
+        .field("Type", channel_type_name(thread.channel_type));
+
+    if let (Some(parent_id), Some(guild)) = (&thread.parent_id, context.cache.get_guild(&guild_id)) {
+        entry = entry.field("Parent channel", channel_name(&guild, parent_id));
+    }
+
+    context.log(&guild_id, entry).await;
+}
+
+async fn log_thread_delete(guild_id: GuildId, thread_id: ChannelId, thread: Arc<Channel>, context: Context) {
+    let entry = LogEntry::new(LogCategory::Channels, "🗑️", "Thread deleted", COLOR_NEGATIVE)
+        .channel(filter_channel(&thread_id, &thread))
+        .field("Thread", thread_line(&thread_id, &thread))
+        .field("Type", channel_type_name(thread.channel_type));
+
+    context.log(&guild_id, entry).await;
+}
+
+async fn log_thread_update(
+    guild_id: GuildId,
+    thread_id: ChannelId,
+    old: Arc<Channel>,
+    new: Arc<Channel>,
+    context: Context,
+) {
+    let mut changes = Vec::new();
+    if old.name != new.name {
+        changes.push(format!("**Name**: {} ➡ {}", old.name, new.name));
+    }
+
+    if let (Some(old_meta), Some(new_meta)) = (&old.thread_meta, &new.thread_meta) {
+        if old_meta.archived != new_meta.archived {
+            changes.push(if new_meta.archived {
+                "**Archived**".to_string()
+            } else {
+                "**Unarchived**".to_string()
+            });
+        }
+        if old_meta.locked != new_meta.locked {
+            changes.push(if new_meta.locked {
+                "**Locked**".to_string()
+            } else {
+                "**Unlocked**".to_string()
+            });
+        }
+    }
+
+    // the last message id and the like also trigger updates, those are not interesting
+    if changes.is_empty() {
+        return;
+    }
+
+    let mut entry = LogEntry::new(LogCategory::Channels, "🧵", "Thread updated", COLOR_NEUTRAL)
+        .channel(filter_channel(&thread_id, &new))
+        .field("Thread", thread_line(&thread_id, &new))
+        .field("Type", channel_type_name(new.channel_type));
+    for change in changes {
+        entry = entry.line(change);
+    }
+
+    context.log(&guild_id, entry).await;
+}
+
+async fn log_thread_members(
+    guild_id: GuildId,
+    thread_id: ChannelId,
+    added: Vec<UserId>,
+    removed: Vec<UserId>,
+    context: Context,
+) {
+    let guild = match context.cache.get_guild(&guild_id) {
+        Some(guild) => guild,
+        None => return,
+    };
+    // only private threads are interesting, anyone can join public ones
+    let thread = match guild.get_channel(&thread_id) {
+        Some(thread) if thread.channel_type == ChannelType::GuildPrivateThread => thread,
+        _ => return,
+    };
+
+    match context.get_guild_info(&guild_id).await {
+        Ok(info) if info.config.logging.thread_members => {}
+        _ => return,
+    }
+
+    let member_entry = |emoji, title: &str, color, user_id: &UserId| {
+        let user = context.cache.get_user(user_id);
+        let roles = guild
+            .get_member(user_id)
+            .map(|member| member.roles.clone())
+            .unwrap_or_default();
+        LogEntry::new(LogCategory::Channels, emoji, title, color)
+            .channel(filter_channel(&thread_id, &thread))
+            .user(*user_id, user.as_ref().is_some_and(|user| user.bot), &roles)
+            .field("User", maybe_name_and_id(user, user_id))
+            .field("Thread", thread_line(&thread_id, &thread))
+    };
+
+    let mut entries = Vec::new();
+    for user_id in &added {
+        entries.push(member_entry("➕", "Added to private thread", COLOR_POSITIVE, user_id));
+    }
+    for user_id in &removed {
+        entries.push(member_entry("➖", "Left private thread", COLOR_NEGATIVE, user_id));
+    }
+
+    for entry in entries {
+        context.log(&guild_id, entry).await;
+    }
+}
@@ -1,7 +1,13 @@
 use std::sync::Arc;
+
 use twilight_model::voice::VoiceState as TwilightVoiceState;
 
+use gearbot_2_lib::datastore::guild::LogCategory;
+use gearbot_2_lib::util::markers::{GuildId, UserId};
+
 use crate::cache::voice_state::VoiceState;
+use crate::cache::Guild;
+use crate::logging::{channel_name, maybe_name_and_id, LogEntry, COLOR_NEGATIVE, COLOR_NEUTRAL, COLOR_POSITIVE};
 use crate::util::bot_context::Context;
 
 pub fn on_voice_state_update(update: TwilightVoiceState, context: &Context) {
@@ -9,7 +15,90 @@ pub fn on_voice_state_update(update: TwilightVoiceState, context: &Context) {
         if let Some(guild) = context.cache.get_guild(&guild_id) {
             let user_id = update.user_id;
             let new = VoiceState::from_state(update).map(Arc::new);
-            let _old = guild.set_voice_state(user_id, new);
+            let old = guild.set_voice_state(user_id, new.clone());
+            context
+                .tasks
+                .spawn(log_voice_update(guild_id, user_id, guild, old, new, context.clone()));
+        }
+    }
+}
+
+async fn log_voice_update(
+    guild_id: GuildId,
+    user_id: UserId,
+    guild: Arc<Guild>,
+    old: Option<Arc<VoiceState>>,
+    new: Option<Arc<VoiceState>>,
+    context: Context,
+) {
+    let user = context.cache.get_user(&user_id);
+    let user_line = maybe_name_and_id(user.as_ref(), user_id);
+    let bot = user.is_some_and(|user| user.bot);
+    let roles = guild
+        .get_member(&user_id)
+        .map(|member| member.roles.clone())
+        .unwrap_or_default();
+    // the channel is the one they are in now, or the one they left
+    let voice_entry = |emoji, title: &str, color, channel_id| {
+        LogEntry::new(LogCategory::Voice, emoji, title, color)
+            .user(user_id, bot, &roles)
+            .channel(channel_id)
+            .field("User", &user_line)
+    };
+
+    let mut entries = Vec::new();
+    match (&old, &new) {
+        (None, Some(new)) => entries.push(
+            voice_entry("📞", "Joined voice", COLOR_POSITIVE, new.connected_to)
+                .field("Channel", channel_name(&guild, &new.connected_to)),
+        ),
+        (Some(old), None) => entries.push(
+            voice_entry("📴", "Left voice", COLOR_NEGATIVE, old.connected_to)
+                .field("Channel", channel_name(&guild, &old.connected_to)),
+        ),
+        (Some(old), Some(new)) => {
+            if old.connected_to != new.connected_to {
+                entries.push(
+                    voice_entry("🔀", "Moved voice channel", COLOR_NEUTRAL, new.connected_to)
+                        .field("From", channel_name(&guild, &old.connected_to))
+                        .field("To", channel_name(&guild, &new.connected_to)),
+                );
+            }
+
+            let channel = channel_name(&guild, &new.connected_to);
+            if old.server_muted != new.server_muted {
+                entries.push(if new.server_muted {
+                    voice_entry("🔇", "Server muted", COLOR_NEGATIVE, new.connected_to).field("Channel", &channel)
+                } else {
+                    voice_entry("🔈", "Server unmuted", COLOR_POSITIVE, new.connected_to).field("Channel", &channel)
+                });
+            }
+            if old.server_deafened != new.server_deafened {
+                entries.push(if new.server_deafened {
+                    voice_entry("🙉", "Server deafened", COLOR_NEGATIVE, new.connected_to).field("Channel", &channel)
+                } else {
+                    voice_entry("👂", "Server undeafened", COLOR_POSITIVE, new.connected_to).field("Channel", &channel)
+                });
+            }
+            if old.streaming != new.streaming {
+                entries.push(if new.streaming {
+                    voice_entry("📺", "Started streaming", COLOR_NEUTRAL, new.connected_to).field("Channel", &channel)
+                } else {
+                    voice_entry("📺", "Stopped streaming", COLOR_NEUTRAL, new.connected_to).field("Channel", &channel)
+                });
+            }
+            if old.video != new.video {
+                entries.push(if new.video {
+                    voice_entry("📹", "Turned on video", COLOR_NEUTRAL, new.connected_to).field("Channel", &channel)
+                } else {
+                    voice_entry("📹", "Turned off video", COLOR_NEUTRAL, new.connected_to).field("Channel", &channel)
+                });
+            }
         }
+        (None, None) => {}
+    }
+
+    for entry in entries {
+        context.log(&guild_id, entry).await;
     }
 }
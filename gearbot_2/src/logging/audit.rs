@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::sync::Mutex as AsyncMutex;
+use twilight_model::guild::audit_log::AuditLog;
+
+use gearbot_2_lib::util::markers::{GuildId, UserId};
+
+/// How long to wait after an event before looking at the audit log, entries don't always show up right away
+pub const AUDIT_LOG_DELAY: Duration = Duration::from_secs(1);
+/// How old an audit log entry can be to still be matched to an event
+pub const AUDIT_LOG_WINDOW: Duration = Duration::from_secs(15);
+/// How long fetched audit logs are kept around
+const CACHE_TIME: Duration = Duration::from_secs(60);
+
+/// Who performed an action according to the audit log
+pub struct Attribution {
+    pub moderator: UserId,
+    pub moderator_name: String,
+    pub reason: Option<String>,
+}
+
+pub struct FetchedAuditLog {
+    pub fetched_at: Instant,
+    pub log: AuditLog,
+}
+
+/// Recently fetched audit logs per guild, so a burst of events for the same guild can share a single request
+#[derive(Default)]
+pub struct AuditLogCache {
+    guilds: Mutex<HashMap<GuildId, Arc<AsyncMutex<Option<FetchedAuditLog>>>>>,
+}
+
+impl AuditLogCache {
+    /// Get the slot for a guild, lock it while fetching so concurrent lookups wait for that instead of making their own request
+    pub fn slot(&self, guild_id: &GuildId) -> Arc<AsyncMutex<Option<FetchedAuditLog>>> {
+        let mut guilds = self.guilds.lock();
+        // drop logs nobody looked at for a while
+        guilds.retain(|_, slot| {
+            slot.try_lock().map_or(true, |fetched| {
+                fetched
+                    .as_ref()
+                    .is_some_and(|fetched| fetched.fetched_at.elapsed() < CACHE_TIME)
+            })
+        });
+        guilds.entry(*guild_id).or_default().clone()
+    }
+}
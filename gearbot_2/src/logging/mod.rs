@@ -0,0 +1,220 @@
+use std::fmt::Display;
+
+use chrono::Utc;
+use twilight_embed_builder::{EmbedBuilder, ImageSource};
+use twilight_model::channel::embed::Embed;
+use twilight_model::channel::ChannelType;
+use twilight_model::user::User as TwilightUser;
+
+use gearbot_2_lib::datastore::guild::LogCategory;
+use gearbot_2_lib::util::markers::{ChannelId, RoleId, UserId};
+use gearbot_2_lib::util::GearResult;
+
+use crate::cache::Guild;
+use crate::logging::audit::Attribution;
+use crate::logging::pump::truncate;
+
+pub mod audit;
+pub mod permissions;
+pub mod pump;
+
+/// Logs are not translated, but shared formatting helpers like the age formatting still need a language
+pub const LOG_LANG: &str = "en_US";
+
+pub const COLOR_POSITIVE: u32 = 0x2ECC71;
+pub const COLOR_NEGATIVE: u32 = 0xE67E22;
+pub const COLOR_NEUTRAL: u32 = 0x3498DB;
+pub const COLOR_ALERT: u32 = 0xE74C3C;
+
+/// Discord rejects embeds with a longer description
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
+/// A single entry for the guild logs. These are assembled by the event handlers and
+/// rendered according to the log style of the guild they are send to.
+/// Logs are not translated so all content in here is plain english.
+pub struct LogEntry {
+    pub category: LogCategory,
+    // what the entry is about, used to apply the filters of the log targets
+    pub channel: Option<ChannelId>,
+    pub user: Option<UserId>,
+    pub bot: bool,
+    pub roles: Vec<RoleId>,
+    emoji: &'static str,
+    title: String,
+    lines: Vec<String>,
+    thumbnail: Option<String>,
+    image: Option<String>,
+    alerts: Vec<String>,
+    color: u32,
+}
+
+impl LogEntry {
+    pub fn new(category: LogCategory, emoji: &'static str, title: impl Into<String>, color: u32) -> Self {
+        LogEntry {
+            category,
+            channel: None,
+            user: None,
+            bot: false,
+            roles: Vec::new(),
+            emoji,
+            title: title.into(),
+            lines: Vec::new(),
+            thumbnail: None,
+            image: None,
+            alerts: Vec::new(),
+            color,
+        }
+    }
+
+    /// The channel this entry is about
+    pub fn channel(mut self, channel_id: ChannelId) -> Self {
+        self.channel = Some(channel_id);
+        self
+    }
+
+    /// The user this entry is about, along with the roles they have (or had) on the server
+    pub fn user(mut self, user_id: UserId, bot: bool, roles: &[RoleId]) -> Self {
+        self.user = Some(user_id);
+        self.bot = bot;
+        self.roles = roles.to_vec();
+        self
+    }
+
+    pub fn line(mut self, line: impl Into<String>) -> Self {
+        self.lines.push(line.into());
+        self
+    }
+
+    pub fn field(self, name: &str, value: impl AsRef<str>) -> Self {
+        self.line(format!("**{}**: {}", name, value.as_ref()))
+    }
+
+    pub fn thumbnail(mut self, url: String) -> Self {
+        self.thumbnail = Some(url);
+        self
+    }
+
+    pub fn image(mut self, url: String) -> Self {
+        self.image = Some(url);
+        self
+    }
+
+    /// Add who performed the action according to the audit log, if we know
+    pub fn attribution(self, attribution: Option<&Attribution>) -> Self {
+        match attribution {
+            Some(attribution) => {
+                let entry = self.field("By", name_and_id(&attribution.moderator_name, attribution.moderator));
+                match &attribution.reason {
+                    Some(reason) => entry.field("Reason", reason),
+                    None => entry,
+                }
+            }
+            None => self,
+        }
+    }
+
+    /// Highlight this entry, alerts are shown above the rest of the content
+    pub fn alert(mut self, alert: impl Into<String>) -> Self {
+        self.alerts.push(alert.into());
+        self.color = COLOR_ALERT;
+        self
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "`[{}]` {} **{}**",
+            Utc::now().format("%H:%M:%S"),
+            self.emoji,
+            self.title
+        );
+        for alert in &self.alerts {
+            text.push_str(&format!("\n🚨 **{}**", alert));
+        }
+        for line in &self.lines {
+            text.push('\n');
+            text.push_str(line);
+        }
+        text
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn to_embed(&self) -> GearResult<Embed> {
+        let mut description = String::new();
+        for alert in &self.alerts {
+            description.push_str(&format!("🚨 **{}**\n", alert));
+        }
+        description.push_str(&self.lines.join("\n"));
+
+        let mut builder = EmbedBuilder::new()
+            .title(format!("{} {}", self.emoji, self.title))
+            .description(truncate(description, MAX_DESCRIPTION_LENGTH))
+            .color(self.color);
+
+        if let Some(thumbnail) = &self.thumbnail {
+            builder = builder.thumbnail(ImageSource::url(thumbnail)?);
+        }
+
+        if let Some(image) = &self.image {
+            builder = builder.image(ImageSource::url(image)?);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+/// Resolve role ids to their names for logging, roles that are no longer cached fall back to their id
+pub fn role_names(guild: &Guild, roles: &[RoleId]) -> String {
+    if roles.is_empty() {
+        return "None".to_string();
+    }
+
+    roles
+        .iter()
+        .map(|role_id| {
+            guild
+                .get_role(role_id)
+                .map_or_else(|| format!("`{}`", role_id), |role| role.name.clone())
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Resolve a channel to its name for logging, falling back to the id if it's not cached
+pub fn channel_name(guild: &Guild, channel_id: &ChannelId) -> String {
+    let channel = guild.get_channel(channel_id);
+    maybe_name_and_id(channel.as_ref().map(|channel| &channel.name), channel_id)
+}
+
+/// How anything with a name (users, roles, channels, ...) shows up in the logs.
+/// Names can change or be misleading, so the id always goes along with it
+pub fn name_and_id(name: impl Display, id: impl Display) -> String {
+    format!("{} (`{}`)", name, id)
+}
+
+/// Same as [`name_and_id`], but only the id if we don't know the name
+pub fn maybe_name_and_id(name: Option<impl Display>, id: impl Display) -> String {
+    match name {
+        Some(name) => name_and_id(name, id),
+        None => format!("`{}`", id),
+    }
+}
+
+/// Message authors come straight from discord instead of the cache
+pub fn author_line(author: &TwilightUser) -> String {
+    name_and_id(format_args!("{}#{:04}", author.name, author.discriminator), author.id)
+}
+
+pub fn channel_type_name(channel_type: ChannelType) -> &'static str {
+    match channel_type {
+        ChannelType::GuildText => "Text channel",
+        ChannelType::GuildVoice => "Voice channel",
+        ChannelType::GuildCategory => "Category",
+        ChannelType::GuildNews => "News channel",
+        ChannelType::GuildStore => "Store channel",
+        ChannelType::GuildStageVoice => "Stage channel",
+        ChannelType::GuildNewsThread => "News thread",
+        ChannelType::GuildPublicThread => "Public thread",
+        ChannelType::GuildPrivateThread => "Private thread",
+        _ => "Channel",
+    }
+}
@@ -0,0 +1,126 @@
+use twilight_model::channel::permission_overwrite::{PermissionOverwrite, PermissionOverwriteType};
+use twilight_model::guild::Permissions;
+
+use crate::cache::Guild;
+use crate::logging::maybe_name_and_id;
+use crate::Cache;
+
+/// Human readable list of permission flags, `None` if there aren't any
+pub fn permission_list(permissions: Permissions) -> String {
+    if permissions.is_empty() {
+        return "None".to_string();
+    }
+
+    // the debug output of the bitflags gives us the flag names separated by pipes
+    format!("{:?}", permissions)
+        .split(" | ")
+        .map(|name| format!("`{}`", name))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Describe how the permission overwrites of a channel changed, one line per affected role or member
+pub fn overwrite_changes(
+    guild: &Guild,
+    cache: &Cache,
+    old: &[PermissionOverwrite],
+    new: &[PermissionOverwrite],
+) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    // everything in the new list, compared to the old one if it was present there
+    for overwrite in new {
+        let (old_allow, old_deny) = old.iter().find(|old| old.kind == overwrite.kind).map_or_else(
+            || (Permissions::empty(), Permissions::empty()),
+            |old| (old.allow, old.deny),
+        );
+        if let Some(change) = describe_change(
+            guild,
+            cache,
+            &overwrite.kind,
+            (old_allow, old_deny),
+            (overwrite.allow, overwrite.deny),
+        ) {
+            changes.push(change);
+        }
+    }
+
+    // overwrites that got removed entirely
+    for overwrite in old {
+        if !new.iter().any(|new| new.kind == overwrite.kind) {
+            if let Some(change) = describe_change(
+                guild,
+                cache,
+                &overwrite.kind,
+                (overwrite.allow, overwrite.deny),
+                (Permissions::empty(), Permissions::empty()),
+            ) {
+                changes.push(change);
+            }
+        }
+    }
+
+    changes
+}
+
+fn describe_change(
+    guild: &Guild,
+    cache: &Cache,
+    target: &PermissionOverwriteType,
+    (old_allow, old_deny): (Permissions, Permissions),
+    (new_allow, new_deny): (Permissions, Permissions),
+) -> Option<String> {
+    let allowed = new_allow - old_allow;
+    let denied = new_deny - old_deny;
+    // no longer explicitly allowed or denied, falling back to the role permissions
+    let reset = (old_allow | old_deny) - (new_allow | new_deny);
+
+    if allowed.is_empty() && denied.is_empty() && reset.is_empty() {
+        return None;
+    }
+
+    let mut parts = Vec::new();
+    if !allowed.is_empty() {
+        parts.push(format!("✅ allowed {}", permission_list(allowed)));
+    }
+    if !denied.is_empty() {
+        parts.push(format!("❌ denied {}", permission_list(denied)));
+    }
+    if !reset.is_empty() {
+        parts.push(format!("➖ reset {}", permission_list(reset)));
+    }
+
+    Some(format!(
+        "**{}**: {}",
+        overwrite_target(guild, cache, target),
+        parts.join("; ")
+    ))
+}
+
+fn overwrite_target(guild: &Guild, cache: &Cache, target: &PermissionOverwriteType) -> String {
+    match target {
+        PermissionOverwriteType::Role(role_id) => {
+            let role = guild.get_role(role_id);
+            format!(
+                "Role {}",
+                maybe_name_and_id(role.as_ref().map(|role| &role.name), role_id)
+            )
+        }
+        PermissionOverwriteType::Member(user_id) => {
+            format!("Member {}", maybe_name_and_id(cache.get_user(user_id), user_id))
+        }
+    }
+}
+
+/// Permissions that allow someone to do serious damage to a server
+pub fn dangerous_permissions() -> Permissions {
+    Permissions::ADMINISTRATOR
+        | Permissions::BAN_MEMBERS
+        | Permissions::KICK_MEMBERS
+        | Permissions::MANAGE_GUILD
+        | Permissions::MANAGE_ROLES
+        | Permissions::MANAGE_CHANNELS
+        | Permissions::MANAGE_WEBHOOKS
+        | Permissions::MANAGE_MESSAGES
+        | Permissions::MENTION_EVERYONE
+}
@@ -0,0 +1,495 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+use tokio::time::sleep;
+use tracing::{error, warn};
+use twilight_http::error::ErrorType;
+use twilight_model::channel::embed::Embed;
+
+use gearbot_2_lib::util::error::GearError;
+use gearbot_2_lib::util::markers::{ChannelId, WebhookId};
+use gearbot_2_lib::util::GearResult;
+
+use crate::util::bot_context::Context;
+use crate::util::Metrics;
+
+/// How long to wait for more entries to come in before sending out what is queued
+const BATCH_WINDOW: Duration = Duration::from_millis(500);
+/// How many entries a single channel can have queued before new ones get dropped
+const MAX_BACKLOG: usize = 500;
+const MAX_ATTEMPTS: u32 = 3;
+/// How long to stick with regular messages after we where denied access to the webhooks of a channel
+const WEBHOOK_DENIED_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+const WEBHOOK_NAME: &str = "GearBot logs";
+
+const MAX_CONTENT_LENGTH: usize = 2000;
+const MAX_EMBEDS: usize = 10;
+const MAX_EMBEDS_LENGTH: usize = 6000;
+
+/// A rendered log entry waiting to be send
+pub enum LogMessage {
+    Text(String),
+    Embed(Box<Embed>),
+}
+
+/// Name and avatar to post as when delivering logs through a webhook
+#[derive(Clone)]
+pub struct WebhookProfile {
+    pub name: Option<String>,
+    pub avatar: Option<String>,
+}
+
+enum CachedWebhook {
+    Webhook { id: WebhookId, token: String },
+    // we are not allowed to manage the webhooks of this channel
+    Denied(Instant),
+}
+
+/// Multiple log messages combined into a single discord message
+enum LogBatch {
+    Text(String),
+    Embeds(Vec<Embed>),
+}
+
+#[derive(Default)]
+struct ChannelQueue {
+    pending: VecDeque<LogMessage>,
+    // only one batch per channel is in flight at any time so entries stay in order
+    sending: bool,
+    dropped: usize,
+    // how to deliver the queued messages, this follows the guild config at the time of the last push
+    webhook: Option<WebhookProfile>,
+}
+
+/// Per channel log queues, these get drained by the pump task started with [`run`].
+/// Entries that come in while a batch is still being send get combined into as few messages as possible
+/// so a flood of events (like a raid) doesn't turn into a flood of api calls
+#[derive(Default)]
+pub struct LogPump {
+    queues: Mutex<HashMap<ChannelId, ChannelQueue>>,
+    webhooks: Mutex<HashMap<ChannelId, CachedWebhook>>,
+    notify: Notify,
+    stopping: AtomicBool,
+}
+
+impl LogPump {
+    /// Queue a message for a log channel, to be send through a webhook if a profile is given
+    pub fn push(&self, channel_id: ChannelId, message: LogMessage, webhook: Option<WebhookProfile>, metrics: &Metrics) {
+        {
+            let mut queues = self.queues.lock();
+            let queue = queues.entry(channel_id).or_default();
+            queue.webhook = webhook;
+            if queue.pending.len() >= MAX_BACKLOG {
+                queue.dropped += 1;
+                metrics.logs_dropped.inc();
+                return;
+            }
+            queue.pending.push_back(message);
+        }
+        self.notify.notify_one();
+    }
+
+    /// Stop waiting for more entries to batch together, the pump will exit once everything queued is send
+    pub fn stop(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.notify.notify_one();
+    }
+
+    fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    fn is_idle(&self) -> bool {
+        self.queues.lock().is_empty()
+    }
+
+    /// Takes the next batch for every channel that isn't already busy sending one
+    fn take_batches(&self) -> Vec<(ChannelId, LogBatch, Option<WebhookProfile>)> {
+        let mut batches = Vec::new();
+        for (channel_id, queue) in self.queues.lock().iter_mut() {
+            if queue.sending || queue.pending.is_empty() {
+                continue;
+            }
+
+            if queue.dropped > 0 {
+                queue.pending.push_front(LogMessage::Text(format!(
+                    "⚠️ **{} log entries were dropped because this channel couldn't keep up**",
+                    queue.dropped
+                )));
+                queue.dropped = 0;
+            }
+
+            if let Some(batch) = next_batch(&mut queue.pending) {
+                queue.sending = true;
+                batches.push((*channel_id, batch, queue.webhook.clone()));
+            }
+        }
+        batches
+    }
+
+    fn finish(&self, channel_id: &ChannelId) {
+        {
+            let mut queues = self.queues.lock();
+            if let Some(queue) = queues.get_mut(channel_id) {
+                queue.sending = false;
+                if queue.pending.is_empty() && queue.dropped == 0 {
+                    queues.remove(channel_id);
+                }
+            }
+        }
+        self.notify.notify_one();
+    }
+
+    fn forget_webhook(&self, channel_id: &ChannelId) {
+        self.webhooks.lock().remove(channel_id);
+    }
+}
+
+/// Drains the log queues until the pump is stopped and everything that was queued got send
+pub async fn run(context: Context) {
+    let pump = &context.log_pump;
+    loop {
+        for (channel_id, batch, webhook) in pump.take_batches() {
+            tokio::spawn(deliver(channel_id, batch, webhook, context.clone()));
+        }
+
+        if pump.is_stopping() && pump.is_idle() {
+            break;
+        }
+
+        pump.notify.notified().await;
+        if !pump.is_stopping() {
+            sleep(BATCH_WINDOW).await;
+        }
+    }
+}
+
+async fn deliver(channel_id: ChannelId, batch: LogBatch, webhook: Option<WebhookProfile>, context: Context) {
+    let mut attempt = 1;
+    loop {
+        let result = match &webhook {
+            Some(profile) => send_webhook_batch(&channel_id, &batch, profile, &context).await,
+            None => send_batch(&channel_id, &batch, &context).await,
+        };
+        match result {
+            Ok(()) => break,
+            Err(e) => {
+                if attempt >= MAX_ATTEMPTS || !should_retry(&e) {
+                    error!(
+                        "Failed to deliver logs to channel {} after {} attempt(s): {}",
+                        channel_id,
+                        attempt,
+                        e.get_log_error()
+                    );
+                    context.metrics.logs_failed.inc();
+                    break;
+                }
+                warn!(
+                    "Failed to deliver logs to channel {} (attempt {}), retrying: {}",
+                    channel_id,
+                    attempt,
+                    e.get_log_error()
+                );
+                sleep(Duration::from_secs(2u64.pow(attempt))).await;
+                attempt += 1;
+            }
+        }
+    }
+
+    context.log_pump.finish(&channel_id);
+}
+
+async fn send_batch(channel_id: &ChannelId, batch: &LogBatch, context: &Context) -> GearResult<()> {
+    match batch {
+        LogBatch::Text(content) => {
+            context
+                .api_client
+                .create_message(*channel_id)
+                .content(content)?
+                .exec()
+                .await?
+        }
+        LogBatch::Embeds(embeds) => {
+            context
+                .api_client
+                .create_message(*channel_id)
+                .embeds(embeds)?
+                .exec()
+                .await?
+        }
+    };
+    Ok(())
+}
+
+/// Sends a batch through the webhook of the channel, creating or recreating it as needed.
+/// Falls back to regular messages when we are not allowed to manage webhooks there
+async fn send_webhook_batch(
+    channel_id: &ChannelId,
+    batch: &LogBatch,
+    profile: &WebhookProfile,
+    context: &Context,
+) -> GearResult<()> {
+    if let Some((webhook_id, token)) = get_webhook(channel_id, context).await? {
+        match execute_webhook(&webhook_id, &token, batch, profile, context).await {
+            // someone deleted our webhook, make a new one and try again
+            Err(e) if has_status(&e, 404) => {
+                context.log_pump.forget_webhook(channel_id);
+                if let Some((webhook_id, token)) = get_webhook(channel_id, context).await? {
+                    return execute_webhook(&webhook_id, &token, batch, profile, context).await;
+                }
+            }
+            result => return result,
+        }
+    }
+
+    send_batch(channel_id, batch, context).await
+}
+
+async fn execute_webhook(
+    webhook_id: &WebhookId,
+    token: &str,
+    batch: &LogBatch,
+    profile: &WebhookProfile,
+    context: &Context,
+) -> GearResult<()> {
+    let mut request = context.api_client.execute_webhook(*webhook_id, token);
+    if let Some(name) = &profile.name {
+        request = request.username(name);
+    }
+    if let Some(avatar) = &profile.avatar {
+        request = request.avatar_url(avatar);
+    }
+
+    match batch {
+        LogBatch::Text(content) => request.content(content)?.exec().await?,
+        LogBatch::Embeds(embeds) => request.embeds(embeds)?.exec().await?,
+    };
+    Ok(())
+}
+
+/// Get the webhook we manage for this channel, re-using one we made before if it still exists
+async fn get_webhook(channel_id: &ChannelId, context: &Context) -> GearResult<Option<(WebhookId, String)>> {
+    match context.log_pump.webhooks.lock().get(channel_id) {
+        Some(CachedWebhook::Webhook { id, token }) => return Ok(Some((*id, token.clone()))),
+        Some(CachedWebhook::Denied(at)) if at.elapsed() < WEBHOOK_DENIED_COOLDOWN => return Ok(None),
+        _ => {}
+    }
+
+    let existing = match context.api_client.channel_webhooks(*channel_id).exec().await {
+        Ok(response) => response
+            .models()
+            .await?
+            .into_iter()
+            .find(|webhook| webhook.application_id == Some(context.bot_id) && webhook.token.is_some()),
+        Err(e) if response_status(&e, 403) => return Ok(denied(channel_id, context)),
+        Err(e) => return Err(e.into()),
+    };
+
+    let webhook = match existing {
+        Some(webhook) => webhook,
+        None => match context
+            .api_client
+            .create_webhook(*channel_id, WEBHOOK_NAME)
+            .exec()
+            .await
+        {
+            Ok(response) => response.model().await?,
+            Err(e) if response_status(&e, 403) => return Ok(denied(channel_id, context)),
+            Err(e) => return Err(e.into()),
+        },
+    };
+
+    // webhooks we create always have a token, this is just to satisfy the model
+    let token = match webhook.token {
+        Some(token) => token,
+        None => return Ok(None),
+    };
+    context.log_pump.webhooks.lock().insert(
+        *channel_id,
+        CachedWebhook::Webhook {
+            id: webhook.id,
+            token: token.clone(),
+        },
+    );
+    Ok(Some((webhook.id, token)))
+}
+
+fn denied(channel_id: &ChannelId, context: &Context) -> Option<(WebhookId, String)> {
+    context
+        .log_pump
+        .webhooks
+        .lock()
+        .insert(*channel_id, CachedWebhook::Denied(Instant::now()));
+    None
+}
+
+fn response_status(error: &twilight_http::Error, code: u16) -> bool {
+    matches!(error.kind(), ErrorType::Response { status, .. } if *status == code)
+}
+
+fn has_status(error: &GearError, code: u16) -> bool {
+    matches!(error, GearError::Twilight(e) if response_status(e, code))
+}
+
+// bad requests, missing permissions or a deleted channel won't fix themselves by trying again
+fn should_retry(error: &GearError) -> bool {
+    matches!(error, GearError::Twilight(_)) && ![400, 403, 404].iter().any(|code| has_status(error, *code))
+}
+
+/// Combines as many queued messages of the same kind as fit in a single discord message
+fn next_batch(pending: &mut VecDeque<LogMessage>) -> Option<LogBatch> {
+    match pending.pop_front()? {
+        LogMessage::Text(text) => {
+            let mut content = truncate(text, MAX_CONTENT_LENGTH);
+            let mut length = content.chars().count();
+            while let Some(LogMessage::Text(next)) = pending.front() {
+                let next_length = next.chars().count();
+                if length + 1 + next_length > MAX_CONTENT_LENGTH {
+                    break;
+                }
+                content.push('\n');
+                content.push_str(next);
+                length += 1 + next_length;
+                pending.pop_front();
+            }
+            Some(LogBatch::Text(content))
+        }
+        LogMessage::Embed(embed) => {
+            let mut length = embed_length(&embed);
+            let mut embeds = vec![*embed];
+            while let Some(LogMessage::Embed(next)) = pending.front() {
+                let next_length = embed_length(next);
+                if embeds.len() >= MAX_EMBEDS || length + next_length > MAX_EMBEDS_LENGTH {
+                    break;
+                }
+                length += next_length;
+                if let Some(LogMessage::Embed(next)) = pending.pop_front() {
+                    embeds.push(*next);
+                }
+            }
+            Some(LogBatch::Embeds(embeds))
+        }
+    }
+}
+
+pub fn truncate(text: String, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text;
+    }
+    let mut truncated = text.chars().take(max - 3).collect::<String>();
+    truncated.push_str("...");
+    truncated
+}
+
+/// The length of all the text in an embed, as counted by discord for the combined embed limit
+fn embed_length(embed: &Embed) -> usize {
+    let mut length = embed.title.as_ref().map_or(0, |title| title.chars().count())
+        + embed
+            .description
+            .as_ref()
+            .map_or(0, |description| description.chars().count());
+    if let Some(footer) = &embed.footer {
+        length += footer.text.chars().count();
+    }
+    for field in &embed.fields {
+        length += field.name.chars().count() + field.value.chars().count();
+    }
+    length
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_embed_builder::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder};
+
+    use super::*;
+
+    fn embed(description: &str) -> LogMessage {
+        LogMessage::Embed(Box::new(EmbedBuilder::new().description(description).build().unwrap()))
+    }
+
+    fn text(content: &str) -> LogMessage {
+        LogMessage::Text(content.to_string())
+    }
+
+    #[test]
+    fn truncates_on_characters() {
+        assert_eq!(truncate("short".to_string(), 5), "short");
+        assert_eq!(truncate("too long".to_string(), 6), "too...");
+        assert_eq!(truncate("ééééé".to_string(), 4), "é...");
+    }
+
+    #[test]
+    fn counts_all_embed_text() {
+        let embed = EmbedBuilder::new()
+            .title("title")
+            .description("description")
+            .footer(EmbedFooterBuilder::new("footer"))
+            .field(EmbedFieldBuilder::new("name", "value"))
+            .build()
+            .unwrap();
+        assert_eq!(embed_length(&embed), 5 + 11 + 6 + 4 + 5);
+    }
+
+    #[test]
+    fn combines_text_until_full() {
+        let long = "a".repeat(1995);
+        let mut pending = VecDeque::from(vec![text("one"), text("two"), text(&long), text("three")]);
+
+        match next_batch(&mut pending) {
+            Some(LogBatch::Text(content)) => assert_eq!(content, "one\ntwo"),
+            _ => panic!("expected a text batch"),
+        }
+        match next_batch(&mut pending) {
+            Some(LogBatch::Text(content)) => assert_eq!(content, long),
+            _ => panic!("expected a text batch"),
+        }
+        match next_batch(&mut pending) {
+            Some(LogBatch::Text(content)) => assert_eq!(content, "three"),
+            _ => panic!("expected a text batch"),
+        }
+        assert!(next_batch(&mut pending).is_none());
+    }
+
+    #[test]
+    fn truncates_oversized_text() {
+        let mut pending = VecDeque::from(vec![text(&"a".repeat(3000)), text("next")]);
+        match next_batch(&mut pending) {
+            Some(LogBatch::Text(content)) => assert_eq!(content.chars().count(), MAX_CONTENT_LENGTH),
+            _ => panic!("expected a text batch"),
+        }
+        assert_eq!(pending.len(), 1);
+    }
+
+    #[test]
+    fn keeps_text_and_embeds_apart() {
+        let mut pending = VecDeque::from(vec![embed("one"), embed("two"), text("three"), embed("four")]);
+
+        match next_batch(&mut pending) {
+            Some(LogBatch::Embeds(embeds)) => assert_eq!(embeds.len(), 2),
+            _ => panic!("expected an embed batch"),
+        }
+        assert!(matches!(next_batch(&mut pending), Some(LogBatch::Text(_))));
+        assert!(matches!(next_batch(&mut pending), Some(LogBatch::Embeds(_))));
+    }
+
+    #[test]
+    fn respects_embed_limits() {
+        let mut pending = (0..12).map(|_| embed("entry")).collect::<VecDeque<_>>();
+        match next_batch(&mut pending) {
+            Some(LogBatch::Embeds(embeds)) => assert_eq!(embeds.len(), MAX_EMBEDS),
+            _ => panic!("expected an embed batch"),
+        }
+        assert_eq!(pending.len(), 2);
+
+        let big = "a".repeat(4000);
+        let mut pending = VecDeque::from(vec![embed(&big), embed(&big)]);
+        match next_batch(&mut pending) {
+            Some(LogBatch::Embeds(embeds)) => assert_eq!(embeds.len(), 1),
+            _ => panic!("expected an embed batch"),
+        }
+        assert_eq!(pending.len(), 1);
+    }
+}
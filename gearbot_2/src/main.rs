@@ -6,8 +6,8 @@ use std::time::Duration;
 use actix_web::{middleware, rt, web, App, HttpServer};
 use futures_util::StreamExt;
 use git_version::git_version;
-use tokio::time::interval;
-use tracing::{error, info, trace};
+use tokio::time::{interval, timeout};
+use tracing::{error, info, trace, warn};
 use twilight_gateway::cluster::{ClusterBuilder, ShardScheme};
 use twilight_model::gateway::event::Event;
 use twilight_model::gateway::payload::outgoing::update_presence::UpdatePresencePayload;
@@ -28,6 +28,8 @@ pub const GIT_VERSION: &str = git_version!();
 pub mod cache;
 mod communication;
 pub mod events;
+mod logging;
+mod moderation;
 pub mod util;
 
 fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -63,6 +65,7 @@ async fn async_main() -> Result<(), Box<dyn Error + Send + Sync>> {
         | Intents::GUILD_MEMBERS
         | Intents::GUILD_BANS
         | Intents::GUILD_EMOJIS
+        | Intents::GUILD_INVITES
         | Intents::GUILD_VOICE_STATES
         | Intents::GUILD_MESSAGES
         | Intents::DIRECT_MESSAGES;
@@ -108,6 +111,34 @@ async fn async_main() -> Result<(), Box<dyn Error + Send + Sync>> {
         bot_id,
     ));
 
+    // start draining the log queues
+    let log_pump = tokio::spawn(logging::pump::run(context.clone()));
+
+    // anti-spam, raid protection and verification challenges only look at recent activity, forget about everyone that went quiet
+    let c = context.clone();
+    let spam_cleanup = tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(5 * 60));
+        loop {
+            interval.tick().await;
+            c.spam_tracker.cleanup();
+            c.raid_tracker.cleanup();
+            c.verification.cleanup();
+        }
+    });
+
+    // keep the scam domain list up to date, the first tick loads it right away
+    let c = context.clone();
+    let phishing_reloader = tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(30 * 60));
+        loop {
+            interval.tick().await;
+            match c.phishing.reload() {
+                Ok(count) => info!("Loaded {} phishing domains", count),
+                Err(e) => error!("Failed to load the phishing domain list: {}", e),
+            }
+        }
+    });
+
     // initialize kafka message listener whenever possible
     tokio::spawn(communication::initialize_when_lonely(context.clone()));
 
@@ -197,6 +228,19 @@ async fn async_main() -> Result<(), Box<dyn Error + Send + Sync>> {
     if let Some(handle) = rotator {
         handle.abort();
     }
+    spam_cleanup.abort();
+    phishing_reloader.abort();
+
+    // let the event and command handlers that are still running finish, they might still have something to log
+    if timeout(Duration::from_secs(20), context.tasks.wait()).await.is_err() {
+        warn!("Timed out waiting for the running handlers, anything they log later won't be send");
+    }
+
+    // send out any logs that are still queued up
+    context.log_pump.stop();
+    if timeout(Duration::from_secs(20), log_pump).await.is_err() {
+        warn!("Timed out flushing the log queues, remaining log entries are lost");
+    }
 
     info!("Bot event loop terminated, giving the final background tasks 30 seconds to finish up...");
 
@@ -0,0 +1,351 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use twilight_model::channel::Message;
+
+use gearbot_2_lib::datastore::guild::{AntiSpam, LogCategory, Punishment, SpamBucket, SpamType};
+use gearbot_2_lib::util::markers::{ChannelId, GuildId, MessageId, UserId};
+
+use crate::logging::{author_line, LogEntry, COLOR_NEGATIVE};
+use crate::moderation::punishment::punish;
+use crate::util::bot_context::Context;
+
+/// Hits older than this are dropped during cleanup, no matter what the buckets are configured to
+const MAX_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Recent hits of a single member, per bucket
+type MemberHits = HashMap<BucketKey, VecDeque<Hit>>;
+
+/// Buckets are identified by their settings so multiple buckets of the same type don't share hits
+type BucketKey = (SpamType, u32, u32);
+
+struct Hit {
+    at: Instant,
+    amount: u32,
+    // hash of the content, only used for duplicate detection
+    content: u64,
+    message: (ChannelId, MessageId),
+}
+
+/// A bucket that went over its limit
+pub struct Violation {
+    pub bucket: SpamBucket,
+    pub count: u32,
+    pub messages: Vec<(ChannelId, MessageId)>,
+}
+
+/// Sliding windows of recent messages per member, these only live in memory on the cluster handling the guild
+#[derive(Default)]
+pub struct SpamTracker {
+    members: Mutex<HashMap<(GuildId, UserId), MemberHits>>,
+}
+
+impl SpamTracker {
+    /// Register a message and return all buckets it pushed over their limit.
+    /// The hits that trigger a bucket are dropped from it so the same messages don't trigger it again
+    pub fn check(&self, guild_id: GuildId, message: &Message, config: &AntiSpam) -> Vec<Violation> {
+        let now = Instant::now();
+        let content = content_hash(&message.content);
+        let mut violations = Vec::new();
+
+        let mut members = self.members.lock();
+        let buckets = members.entry((guild_id, message.author.id)).or_default();
+
+        for bucket in &config.buckets {
+            let amount = amount(bucket.kind, message);
+            if amount == 0 {
+                continue;
+            }
+
+            let window = Duration::from_secs(bucket.seconds as u64);
+            let hits = buckets.entry((bucket.kind, bucket.limit, bucket.seconds)).or_default();
+            while matches!(hits.front(), Some(hit) if now.duration_since(hit.at) > window) {
+                hits.pop_front();
+            }
+            hits.push_back(Hit {
+                at: now,
+                amount,
+                content,
+                message: (message.channel_id, message.id),
+            });
+
+            let relevant = |hit: &Hit| bucket.kind != SpamType::DuplicateMessages || hit.content == content;
+            let count = hits
+                .iter()
+                .filter(|hit| relevant(hit))
+                .map(|hit| hit.amount)
+                .sum::<u32>();
+            if count > bucket.limit {
+                let messages = hits.iter().filter(|hit| relevant(hit)).map(|hit| hit.message).collect();
+                // only forget the hits that tripped it, other duplicates can still build up
+                hits.retain(|hit| !relevant(hit));
+                violations.push(Violation {
+                    bucket: bucket.clone(),
+                    count,
+                    messages,
+                });
+            }
+        }
+
+        violations
+    }
+
+    /// Drop members that haven't send anything in a while
+    pub fn cleanup(&self) {
+        let now = Instant::now();
+        self.members.lock().retain(|_, buckets| {
+            buckets.retain(|_, hits| {
+                hits.retain(|hit| now.duration_since(hit.at) < MAX_WINDOW);
+                !hits.is_empty()
+            });
+            !buckets.is_empty()
+        });
+    }
+}
+
+/// Run a new message through the anti-spam buckets and punish the author if needed
+pub async fn check_message(guild_id: GuildId, message: &Message, config: &AntiSpam, context: &Context) {
+    // webhooks and bots are not ours to police
+    if message.author.bot || message.webhook_id.is_some() || config.exempt_channels.contains(&message.channel_id) {
+        return;
+    }
+    if let Some(member) = &message.member {
+        if member.roles.iter().any(|role| config.exempt_roles.contains(role)) {
+            return;
+        }
+    }
+    if matches!(context.cache.get_guild(&guild_id), Some(guild) if guild.owner == message.author.id) {
+        return;
+    }
+
+    let violations = context.spam_tracker.check(guild_id, message, config);
+    if violations.is_empty() {
+        return;
+    }
+
+    // when multiple buckets trigger at once only punish once, with the combined punishments
+    let mut punishments: Vec<Punishment> = Vec::new();
+    let mut messages = Vec::new();
+    let mut triggers = Vec::new();
+    for violation in violations {
+        triggers.push(format!(
+            "{} ({}/{} in {}s)",
+            spam_type_name(violation.bucket.kind),
+            violation.count,
+            violation.bucket.limit,
+            violation.bucket.seconds
+        ));
+        for punishment in violation.bucket.punishments {
+            merge_punishment(&mut punishments, punishment);
+        }
+        for message in violation.messages {
+            if !messages.contains(&message) {
+                messages.push(message);
+            }
+        }
+    }
+
+    let user_id = message.author.id;
+    let reason = format!("Anti-spam: {}", triggers.join(", "));
+    let outcomes = punish(&guild_id, &user_id, &punishments, &reason, &messages, context).await;
+
+    let roles = message
+        .member
+        .as_ref()
+        .map_or_else(Vec::new, |member| member.roles.clone());
+    let mut entry = LogEntry::new(LogCategory::Moderation, "🚫", "Spam detected", COLOR_NEGATIVE)
+        .user(user_id, message.author.bot, &roles)
+        .channel(message.channel_id)
+        .field("User", author_line(&message.author))
+        .field("Channel", format!("<#{}>", message.channel_id))
+        .field("Triggered", triggers.join(", "));
+    for outcome in outcomes {
+        entry = entry.line(outcome);
+    }
+    context.log(&guild_id, entry).await;
+}
+
+// timeouts get combined into the longest one, everything else only needs to happen once
+fn merge_punishment(punishments: &mut Vec<Punishment>, punishment: Punishment) {
+    if let Punishment::Timeout { minutes } = punishment {
+        for existing in punishments.iter_mut() {
+            if let Punishment::Timeout { minutes: existing } = existing {
+                *existing = (*existing).max(minutes);
+                return;
+            }
+        }
+    }
+    if !punishments.contains(&punishment) {
+        punishments.push(punishment);
+    }
+}
+
+fn amount(kind: SpamType, message: &Message) -> u32 {
+    let content = &message.content;
+    match kind {
+        SpamType::Messages => 1,
+        // messages with only attachments or stickers all have the same empty content
+        SpamType::DuplicateMessages => !content.trim().is_empty() as u32,
+        SpamType::Mentions => {
+            (message.mentions.len() + message.mention_roles.len() + message.mention_everyone as usize) as u32
+        }
+        SpamType::Links => (content.matches("http://").count() + content.matches("https://").count()) as u32,
+        SpamType::Attachments => message.attachments.len() as u32,
+        SpamType::Emoji => count_emoji(content),
+        SpamType::Newlines => content.matches('\n').count() as u32,
+    }
+}
+
+fn count_emoji(content: &str) -> u32 {
+    let custom = content.matches("<:").count() + content.matches("<a:").count();
+    let unicode = content
+        .chars()
+        .filter(|c| matches!(*c as u32, 0x1F300..=0x1FAFF | 0x2600..=0x27BF))
+        .count();
+    (custom + unicode) as u32
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.trim().to_lowercase().hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn spam_type_name(kind: SpamType) -> &'static str {
+    match kind {
+        SpamType::Messages => "Too many messages",
+        SpamType::DuplicateMessages => "Duplicate messages",
+        SpamType::Mentions => "Too many mentions",
+        SpamType::Links => "Too many links",
+        SpamType::Attachments => "Too many attachments",
+        SpamType::Emoji => "Too many emoji",
+        SpamType::Newlines => "Too many newlines",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: u64, content: &str) -> Message {
+        serde_json::from_value(serde_json::json!({
+            "id": id.to_string(),
+            "channel_id": "2",
+            "author": {"id": "3", "username": "spammer", "discriminator": "0001", "avatar": null},
+            "content": content,
+            "timestamp": "2022-01-01T00:00:00+00:00",
+            "edited_timestamp": null,
+            "tts": false,
+            "mention_everyone": false,
+            "mentions": [],
+            "mention_roles": [],
+            "attachments": [],
+            "embeds": [],
+            "pinned": false,
+            "type": 0
+        }))
+        .unwrap()
+    }
+
+    fn config(buckets: &[(SpamType, u32, u32)]) -> AntiSpam {
+        AntiSpam {
+            enabled: true,
+            buckets: buckets
+                .iter()
+                .map(|(kind, limit, seconds)| SpamBucket {
+                    kind: *kind,
+                    limit: *limit,
+                    seconds: *seconds,
+                    punishments: vec![Punishment::Delete],
+                })
+                .collect(),
+            exempt_roles: vec![],
+            exempt_channels: vec![],
+        }
+    }
+
+    #[test]
+    fn triggers_over_the_limit() {
+        let tracker = SpamTracker::default();
+        let config = config(&[(SpamType::Messages, 2, 60)]);
+        let guild_id = GuildId::new(1);
+
+        assert!(tracker.check(guild_id, &message(1, "a"), &config).is_empty());
+        assert!(tracker.check(guild_id, &message(2, "b"), &config).is_empty());
+        let violations = tracker.check(guild_id, &message(3, "c"), &config);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].count, 3);
+        assert_eq!(violations[0].messages.len(), 3);
+
+        // the hits that triggered are gone, so the next message starts over
+        assert!(tracker.check(guild_id, &message(4, "d"), &config).is_empty());
+    }
+
+    #[test]
+    fn duplicates_only_count_the_same_content() {
+        let tracker = SpamTracker::default();
+        let config = config(&[(SpamType::DuplicateMessages, 1, 60)]);
+        let guild_id = GuildId::new(1);
+
+        assert!(tracker.check(guild_id, &message(1, "hello"), &config).is_empty());
+        assert!(tracker.check(guild_id, &message(2, "other"), &config).is_empty());
+        let violations = tracker.check(guild_id, &message(3, "HELLO "), &config);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].messages,
+            vec![
+                (ChannelId::new(2), MessageId::new(1)),
+                (ChannelId::new(2), MessageId::new(3))
+            ]
+        );
+
+        // tripping on one message doesn't forget the other duplicates
+        assert_eq!(tracker.check(guild_id, &message(4, "other"), &config).len(), 1);
+    }
+
+    #[test]
+    fn buckets_of_the_same_type_are_separate() {
+        let tracker = SpamTracker::default();
+        let config = config(&[(SpamType::Messages, 1, 60), (SpamType::Messages, 3, 60)]);
+        let guild_id = GuildId::new(1);
+
+        assert!(tracker.check(guild_id, &message(1, "a"), &config).is_empty());
+        let violations = tracker.check(guild_id, &message(2, "b"), &config);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].bucket.limit, 1);
+
+        // resetting the small bucket left the bigger one alone
+        assert!(tracker.check(guild_id, &message(3, "c"), &config).is_empty());
+        let violations = tracker.check(guild_id, &message(4, "d"), &config);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[1].count, 4);
+    }
+
+    #[test]
+    fn members_are_tracked_per_guild() {
+        let tracker = SpamTracker::default();
+        let config = config(&[(SpamType::Messages, 1, 60)]);
+
+        assert!(tracker.check(GuildId::new(1), &message(1, "a"), &config).is_empty());
+        assert!(tracker.check(GuildId::new(2), &message(2, "b"), &config).is_empty());
+    }
+
+    #[test]
+    fn merges_timeouts() {
+        let mut punishments = vec![Punishment::Delete, Punishment::Timeout { minutes: 10 }];
+        merge_punishment(&mut punishments, Punishment::Timeout { minutes: 60 });
+        merge_punishment(&mut punishments, Punishment::Delete);
+        merge_punishment(&mut punishments, Punishment::Kick);
+        assert_eq!(
+            punishments,
+            vec![
+                Punishment::Delete,
+                Punishment::Timeout { minutes: 60 },
+                Punishment::Kick
+            ]
+        );
+    }
+}
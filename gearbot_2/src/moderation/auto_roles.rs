@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use tokio::time::sleep;
+use twilight_http::request::AuditLogReason;
+
+use gearbot_2_lib::datastore::guild::{AutoRole, LogCategory};
+use gearbot_2_lib::util::markers::{GuildId, RoleId, UserId};
+use gearbot_2_lib::util::GearResult;
+
+use crate::cache::Guild;
+use crate::logging::{name_and_id, role_names, LogEntry, COLOR_NEGATIVE};
+use crate::util::bot_context::Context;
+
+const AUTO_ROLE_REASON: &str = "Auto roles";
+
+/// Hand out the auto roles to a member that just joined, or that just passed membership screening
+pub async fn assign_roles(
+    guild_id: GuildId,
+    user_id: UserId,
+    passed_screening: bool,
+    context: Context,
+) -> GearResult<()> {
+    let info = context.get_guild_info(&guild_id).await?;
+    let config = &info.config.auto_roles;
+    if !config.enabled || config.roles.is_empty() {
+        return Ok(());
+    }
+
+    let member = match context.cache.get_guild_member(&guild_id, &user_id) {
+        Some(member) => member,
+        None => return Ok(()),
+    };
+    if passed_screening {
+        // they already got everything when they joined
+        if !config.after_screening {
+            return Ok(());
+        }
+    } else if config.after_screening && member.pending {
+        return Ok(());
+    }
+
+    let bot = member.user().bot;
+    let mut roles = config
+        .roles
+        .iter()
+        .filter(|auto_role| !(bot && auto_role.exclude_bots))
+        .cloned()
+        .collect::<Vec<AutoRole>>();
+    roles.sort_by_key(|auto_role| auto_role.delay);
+
+    let mut waited = 0;
+    let mut failures = Vec::new();
+    for auto_role in roles {
+        if auto_role.delay > waited {
+            sleep(Duration::from_secs(auto_role.delay - waited)).await;
+            waited = auto_role.delay;
+        }
+
+        let guild = match context.cache.get_guild(&guild_id) {
+            Some(guild) => guild,
+            None => return Ok(()),
+        };
+        match guild.get_member(&user_id) {
+            Some(member) if member.roles.contains(&auto_role.role) => continue,
+            Some(_) => {}
+            // left before the delay was up
+            None => break,
+        }
+
+        if let Err(problem) = check_hierarchy(&guild, &auto_role.role, &context) {
+            failures.push(problem);
+            continue;
+        }
+        if let Err(e) = add_role(&guild_id, &user_id, &auto_role.role, &context).await {
+            failures.push(format!(
+                "{}: {}",
+                role_names(&guild, &[auto_role.role]),
+                e.get_log_error()
+            ));
+        }
+    }
+
+    if !failures.is_empty() {
+        let user = member.user();
+        let mut entry = LogEntry::new(LogCategory::Members, "🏷️", "Auto roles failed", COLOR_NEGATIVE)
+            .user(user_id, user.bot, &member.roles)
+            .field("User", name_and_id(&user, user_id));
+        for failure in failures {
+            entry = entry.line(format!("❌ {}", failure));
+        }
+        context.log(&guild_id, entry).await;
+    }
+
+    Ok(())
+}
+
+/// Catch the roles we can't hand out up front, so the logs can say why instead of a bare api error
+fn check_hierarchy(guild: &Guild, role_id: &RoleId, context: &Context) -> Result<(), String> {
+    let role = match guild.get_role(role_id) {
+        Some(role) => role,
+        None => return Err(format!("`{}`: the role no longer exists", role_id)),
+    };
+    if role.managed {
+        return Err(format!(
+            "{}: the role is managed by an integration and can't be handed out",
+            role.name
+        ));
+    }
+
+    // can't tell without ourselves in the cache, leave it up to the api
+    let own_member = match guild.get_member(&context.bot_id.cast()) {
+        Some(member) => member,
+        None => return Ok(()),
+    };
+    let highest = own_member
+        .roles
+        .iter()
+        .filter_map(|role| guild.get_role(role))
+        .map(|role| role.position)
+        .max()
+        .unwrap_or(0);
+    if role.position >= highest {
+        return Err(format!(
+            "{}: the role is not below my highest role, move my role above it to hand it out",
+            role.name
+        ));
+    }
+
+    Ok(())
+}
+
+async fn add_role(guild_id: &GuildId, user_id: &UserId, role_id: &RoleId, context: &Context) -> GearResult<()> {
+    context
+        .api_client
+        .add_guild_member_role(*guild_id, *user_id, *role_id)
+        .reason(AUTO_ROLE_REASON)?
+        .exec()
+        .await?;
+    Ok(())
+}
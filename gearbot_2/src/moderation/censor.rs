@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+use regex::{Regex, RegexBuilder};
+use tracing::warn;
+use twilight_http::error::ErrorType;
+use twilight_model::user::User as TwilightUser;
+
+use gearbot_2_lib::datastore::guild::{Censoring, LogCategory};
+use gearbot_2_lib::util::markers::{ChannelId, GuildId, MessageId, RoleId};
+
+use crate::logging::{author_line, LogEntry, COLOR_NEGATIVE};
+use crate::moderation::compile_regexes;
+use crate::util::bot_context::Context;
+
+const INVITE_PATTERN: &str = r"(?i)(?:discord(?:app)?\.com/invite|discord\.gg)/([a-z0-9-]+)";
+const LINK_PATTERN: &str = r"(?i)https?://([^/\s:?#<>]+)";
+
+/// The censor config of a guild compiled into something we can match against quickly
+pub struct CensorFilters {
+    words: Option<Regex>,
+    regexes: Vec<Regex>,
+    invites: Option<Regex>,
+    links: Option<Regex>,
+    // invite codes we already looked up, nothing if the invite is no longer valid
+    resolved_invites: Mutex<HashMap<String, Option<GuildId>>>,
+}
+
+impl CensorFilters {
+    pub fn compile(guild_id: &GuildId, config: &Censoring) -> Self {
+        let words = if config.words.is_empty() {
+            None
+        } else {
+            let pattern = config
+                .words
+                .iter()
+                .map(|word| word_pattern(word))
+                .collect::<Vec<_>>()
+                .join("|");
+            match RegexBuilder::new(&pattern).case_insensitive(true).build() {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    warn!("Failed to compile the censored words for guild {}: {}", guild_id, e);
+                    None
+                }
+            }
+        };
+
+        let regexes = compile_regexes(guild_id, &config.regexes, "censor");
+
+        let check_links = !config.blocked_domains.is_empty() || !config.allowed_domains.is_empty();
+
+        CensorFilters {
+            words,
+            regexes,
+            invites: config.invites.then(|| Regex::new(INVITE_PATTERN).unwrap()),
+            links: check_links.then(|| Regex::new(LINK_PATTERN).unwrap()),
+            resolved_invites: Default::default(),
+        }
+    }
+
+    /// Find the first rule the content breaks, described for the logs
+    async fn find_match(
+        &self,
+        guild_id: &GuildId,
+        content: &str,
+        config: &Censoring,
+        context: &Context,
+    ) -> Option<String> {
+        if let Some(word) = self.words.as_ref().and_then(|words| words.find(content)) {
+            return Some(format!("Censored word `{}`", word.as_str()));
+        }
+
+        if let Some(regex) = self.regexes.iter().find(|regex| regex.is_match(content)) {
+            return Some(format!("Censored pattern `{}`", regex.as_str()));
+        }
+
+        if let Some(invites) = &self.invites {
+            let codes = invites
+                .captures_iter(content)
+                .map(|captures| captures[1].to_string())
+                .collect::<Vec<_>>();
+            for code in codes {
+                match self.resolve_invite(&code, context).await {
+                    Some(Some(target)) if target == *guild_id || config.invite_allowlist.contains(&target) => {}
+                    // couldn't look it up right now, better to let one through then to remove valid messages
+                    None => {}
+                    Some(_) => return Some(format!("Invite to another server (`{}`)", code)),
+                }
+            }
+        }
+
+        if let Some(links) = &self.links {
+            for captures in links.captures_iter(content) {
+                let host = captures[1].to_lowercase();
+                if config
+                    .blocked_domains
+                    .iter()
+                    .any(|domain| domain_matches(&host, domain))
+                {
+                    return Some(format!("Blocked domain `{}`", host));
+                }
+                if !config.allowed_domains.is_empty()
+                    && !config
+                        .allowed_domains
+                        .iter()
+                        .any(|domain| domain_matches(&host, domain))
+                {
+                    return Some(format!("Domain not allowed `{}`", host));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Look up what guild an invite points to.
+    /// Returns nothing if we failed to find out, and no guild if the invite is invalid
+    async fn resolve_invite(&self, code: &str, context: &Context) -> Option<Option<GuildId>> {
+        if let Some(resolved) = self.resolved_invites.lock().get(code) {
+            return Some(*resolved);
+        }
+
+        let resolved = match context.api_client.invite(code).exec().await {
+            Ok(response) => match response.model().await {
+                Ok(invite) => invite.guild.map(|guild| guild.id),
+                Err(e) => {
+                    warn!("Failed to deserialize invite {}: {:?}", code, e);
+                    return None;
+                }
+            },
+            Err(e) if matches!(e.kind(), ErrorType::Response { status, .. } if *status == 404) => None,
+            Err(e) => {
+                warn!("Failed to look up invite {}: {}", code, e);
+                return None;
+            }
+        };
+
+        self.resolved_invites.lock().insert(code.to_string(), resolved);
+        Some(resolved)
+    }
+}
+
+/// Only match whole words, word boundaries only make sense on sides that start or end with a word character
+/// (`c++` would otherwise never match)
+fn word_pattern(word: &str) -> String {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let start = if word.starts_with(is_word_char) { r"\b" } else { "" };
+    let end = if word.ends_with(is_word_char) { r"\b" } else { "" };
+    format!("{}{}{}", start, regex::escape(word), end)
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches("*.").to_lowercase();
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Run a new or edited message through the censor of the guild and remove it if it breaks any of the rules
+#[allow(clippy::too_many_arguments)]
+pub async fn censor_message(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    author: &TwilightUser,
+    roles: &[RoleId],
+    content: &str,
+    config: &Censoring,
+    context: &Context,
+) {
+    if author.bot || config.exempt_channels.contains(&channel_id) {
+        return;
+    }
+    if roles.iter().any(|role| config.exempt_roles.contains(role)) {
+        return;
+    }
+    if matches!(context.cache.get_guild(&guild_id), Some(guild) if guild.owner == author.id) {
+        return;
+    }
+
+    let filters = match context.get_censor_filters(&guild_id).await {
+        Ok(filters) => filters,
+        Err(e) => {
+            warn!("Failed to get the censor filters for guild {}: {}", guild_id, e);
+            return;
+        }
+    };
+    let rule = match filters.find_match(&guild_id, content, config, context).await {
+        Some(rule) => rule,
+        None => return,
+    };
+
+    let mut entry = LogEntry::new(LogCategory::Moderation, "🤐", "Message censored", COLOR_NEGATIVE)
+        .user(author.id, author.bot, roles)
+        .channel(channel_id)
+        .field("User", author_line(author))
+        .field("Channel", format!("<#{}>", channel_id))
+        .field("Rule", &rule)
+        .field("Content", content);
+
+    if let Err(e) = context.api_client.delete_message(channel_id, message_id).exec().await {
+        entry = entry.line(format!("❌ Failed to remove the message: {}", e));
+    }
+
+    context.log(&guild_id, entry).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile_words(words: &[&str]) -> Regex {
+        let config = Censoring {
+            words: words.iter().map(|word| word.to_string()).collect(),
+            ..Censoring::default()
+        };
+        CensorFilters::compile(&GuildId::new(1), &config).words.unwrap()
+    }
+
+    #[test]
+    fn matches_whole_words() {
+        let words = compile_words(&["bad", "c++"]);
+        assert!(words.is_match("this is bad"));
+        assert!(words.is_match("BAD!"));
+        assert!(words.is_match("I write c++ for a living"));
+        assert!(!words.is_match("badge"));
+        assert!(!words.is_match("a sandbad"));
+    }
+
+    #[test]
+    fn escapes_words() {
+        assert_eq!(word_pattern("bad"), r"\bbad\b");
+        assert_eq!(word_pattern("c++"), r"\bc\+\+");
+        assert_eq!(word_pattern(".net"), r"\.net\b");
+        assert!(!compile_words(&["a.c"]).is_match("abc"));
+    }
+
+    #[test]
+    fn matches_domains_and_subdomains() {
+        assert!(domain_matches("example.com", "example.com"));
+        assert!(domain_matches("cdn.example.com", "example.com"));
+        assert!(domain_matches("cdn.example.com", "*.example.com"));
+        assert!(domain_matches("example.com", " Example.COM "));
+        assert!(!domain_matches("notexample.com", "example.com"));
+        assert!(!domain_matches("example.com.evil", "example.com"));
+    }
+
+    #[test]
+    fn extracts_invites_and_hosts() {
+        let invites = Regex::new(INVITE_PATTERN).unwrap();
+        let codes = invites
+            .captures_iter("join discord.gg/abc-1 or https://discord.com/invite/XyZ")
+            .map(|captures| captures[1].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(codes, vec!["abc-1", "XyZ"]);
+
+        let links = Regex::new(LINK_PATTERN).unwrap();
+        let hosts = links
+            .captures_iter("<https://Example.com/path> http://sub.test.org:8080?x")
+            .map(|captures| captures[1].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(hosts, vec!["Example.com", "sub.test.org"]);
+    }
+
+    #[test]
+    fn skips_invalid_regexes() {
+        let config = Censoring {
+            regexes: vec!["(".to_string(), "fo+".to_string()],
+            ..Censoring::default()
+        };
+        let filters = CensorFilters::compile(&GuildId::new(1), &config);
+        assert_eq!(filters.regexes.len(), 1);
+        assert!(filters.regexes[0].is_match("fooo"));
+        assert!(filters.invites.is_none());
+        assert!(filters.links.is_none());
+    }
+}
@@ -0,0 +1,207 @@
+use std::slice;
+
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+
+use gearbot_2_lib::datastore::guild::{EscalationStep, GuildDatastore, InfractionType, LogCategory};
+use gearbot_2_lib::util::markers::{GuildId, UserId};
+use gearbot_2_lib::util::GearResult;
+
+use crate::logging::{maybe_name_and_id, LogEntry, COLOR_NEGATIVE};
+use crate::moderation::punishment::{describe_punishment, punish_escalated};
+use crate::util::bot_context::Context;
+
+/// Store an infraction in the moderation history and apply the escalation step it triggers, if any.
+/// The chain holds the escalation steps that led to this infraction, steps in there are skipped so they can't loop.
+/// Boxed since applying a step records the punishment, which ends up back here
+pub fn record_infraction<'a>(
+    guild_id: &'a GuildId,
+    target: &'a UserId,
+    moderator: Option<&'a UserId>,
+    kind: InfractionType,
+    reason: Option<&'a str>,
+    chain: &'a [String],
+    context: &'a Context,
+) -> BoxFuture<'a, GearResult<()>> {
+    async move { record(guild_id, target, moderator, kind, reason, chain, context).await }.boxed()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn record(
+    guild_id: &GuildId,
+    target: &UserId,
+    moderator: Option<&UserId>,
+    kind: InfractionType,
+    reason: Option<&str>,
+    chain: &[String],
+    context: &Context,
+) -> GearResult<()> {
+    let info = context.get_guild_info(guild_id).await?;
+    let datastore = GuildDatastore::new(&context.datastore, &info.encryption_key, guild_id);
+    // unbans only close old bans, they're not something done against someone
+    datastore
+        .store_infraction(target, moderator, kind, reason, kind != InfractionType::Unban)
+        .await?;
+
+    let config = &info.config.escalation;
+    if !config.enabled {
+        return Ok(());
+    }
+
+    // only one step gets applied
+    for (step, description) in candidate_steps(&config.steps, kind, chain) {
+        let count = datastore.count_recent_infractions(target, kind, step.days).await?;
+        if !reaches_threshold(step, count) {
+            continue;
+        }
+
+        let mut chain = chain.to_vec();
+        chain.push(description);
+        let escalation_reason = format!("Escalation: {}", chain.join(", then "));
+        let outcomes = punish_escalated(
+            guild_id,
+            target,
+            slice::from_ref(&step.punishment),
+            &escalation_reason,
+            &[],
+            &chain,
+            context,
+        )
+        .await;
+
+        let user = context.cache.get_user(target);
+        let mut entry = LogEntry::new(LogCategory::Moderation, "⏫", "Punishment escalated", COLOR_NEGATIVE)
+            .user(
+                *target,
+                user.as_ref().is_some_and(|user| user.bot),
+                &context
+                    .cache
+                    .get_guild_member(guild_id, target)
+                    .map(|member| member.roles.clone())
+                    .unwrap_or_default(),
+            )
+            .field("User", maybe_name_and_id(user.as_ref(), target));
+        if let Some(reason) = reason_of(reason) {
+            entry = entry.field("Triggered by", reason);
+        }
+        for (i, step) in chain.iter().enumerate() {
+            entry = entry.line(format!("{}. {}", i + 1, step));
+        }
+        for outcome in outcomes {
+            entry = entry.line(outcome);
+        }
+        context.log(guild_id, entry).await;
+        break;
+    }
+
+    Ok(())
+}
+
+/// The steps an infraction of this kind could trigger, most severe first.
+/// Steps that are already part of the chain are left out so they can't loop
+fn candidate_steps<'a>(
+    steps: &'a [EscalationStep],
+    kind: InfractionType,
+    chain: &[String],
+) -> Vec<(&'a EscalationStep, String)> {
+    steps
+        .iter()
+        .rev()
+        .filter(|step| step.infraction == kind)
+        .map(|step| (step, describe_step(step)))
+        .filter(|(_, description)| !chain.contains(description))
+        .collect()
+}
+
+/// Steps fire on the infraction that reaches their count, not again on every infraction after that within the window
+fn reaches_threshold(step: &EscalationStep, count: i64) -> bool {
+    count == step.count.max(1) as i64
+}
+
+// the reason of an escalated infraction is the chain itself, no need to show that twice
+fn reason_of(reason: Option<&str>) -> Option<&str> {
+    reason.filter(|reason| !reason.starts_with("Escalation: "))
+}
+
+fn describe_step(step: &EscalationStep) -> String {
+    format!(
+        "{} {} in {} day(s) → {}",
+        step.count,
+        infraction_name(step.infraction),
+        step.days,
+        describe_punishment(&step.punishment)
+    )
+}
+
+fn infraction_name(kind: InfractionType) -> &'static str {
+    match kind {
+        InfractionType::Ban => "ban(s)",
+        InfractionType::Unban => "unban(s)",
+        InfractionType::Kick => "kick(s)",
+        InfractionType::Timeout => "timeout(s)",
+        InfractionType::Warn => "warning(s)",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gearbot_2_lib::datastore::guild::Punishment;
+
+    use super::*;
+
+    fn step(infraction: InfractionType, count: u32, punishment: Punishment) -> EscalationStep {
+        EscalationStep {
+            infraction,
+            count,
+            days: 7,
+            punishment,
+        }
+    }
+
+    fn ladder() -> Vec<EscalationStep> {
+        vec![
+            step(InfractionType::Warn, 3, Punishment::Timeout { minutes: 60 }),
+            step(InfractionType::Warn, 5, Punishment::Kick),
+            step(InfractionType::Timeout, 2, Punishment::Ban),
+        ]
+    }
+
+    #[test]
+    fn fires_once_when_reaching_the_count() {
+        let steps = ladder();
+        let warns = &steps[0];
+        assert!(!reaches_threshold(warns, 2));
+        assert!(reaches_threshold(warns, 3));
+        assert!(!reaches_threshold(warns, 4));
+        assert!(!reaches_threshold(warns, 5));
+    }
+
+    #[test]
+    fn zero_counts_fire_on_the_first_infraction() {
+        let step = step(InfractionType::Warn, 0, Punishment::Kick);
+        assert!(reaches_threshold(&step, 1));
+        assert!(!reaches_threshold(&step, 2));
+    }
+
+    #[test]
+    fn candidates_are_most_severe_first_and_of_the_same_kind() {
+        let steps = ladder();
+        let candidates = candidate_steps(&steps, InfractionType::Warn, &[]);
+        let counts = candidates.iter().map(|(step, _)| step.count).collect::<Vec<_>>();
+        assert_eq!(counts, vec![5, 3]);
+
+        let candidates = candidate_steps(&steps, InfractionType::Timeout, &[]);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].0.punishment, Punishment::Ban);
+
+        assert!(candidate_steps(&steps, InfractionType::Kick, &[]).is_empty());
+    }
+
+    #[test]
+    fn steps_in_the_chain_are_skipped() {
+        let steps = ladder();
+        let chain = vec![describe_step(&steps[2])];
+        assert!(candidate_steps(&steps, InfractionType::Timeout, &chain).is_empty());
+        assert_eq!(candidate_steps(&steps, InfractionType::Warn, &chain).len(), 2);
+    }
+}
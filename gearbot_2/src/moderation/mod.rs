@@ -0,0 +1,30 @@
+use regex::Regex;
+use tracing::debug;
+
+use gearbot_2_lib::util::markers::GuildId;
+
+pub mod anti_spam;
+pub mod auto_roles;
+pub mod censor;
+pub mod escalation;
+pub mod names;
+pub mod phishing;
+pub mod punishment;
+pub mod raid;
+pub mod sticky_roles;
+pub mod verification;
+
+/// Compile the regexes a guild configured. These are user input so bad ones are to be expected,
+/// skip them instead of rejecting everything
+pub fn compile_regexes(guild_id: &GuildId, patterns: &[String], kind: &str) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                debug!("Skipping invalid {} regex for guild {}: {}", kind, guild_id, e);
+                None
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,246 @@
+use regex::Regex;
+use tracing::warn;
+use twilight_http::request::AuditLogReason;
+
+use gearbot_2_lib::datastore::guild::{LogCategory, NamePolicy};
+use gearbot_2_lib::util::confusables::{fold_confusables, is_combining, strip_combining};
+use gearbot_2_lib::util::markers::{GuildId, UserId};
+use gearbot_2_lib::util::GearResult;
+
+use crate::logging::{name_and_id, LogEntry, COLOR_NEGATIVE};
+use crate::moderation::compile_regexes;
+use crate::util::bot_context::Context;
+
+const MAX_NICKNAME_LENGTH: usize = 32;
+/// Used when nothing is left of a name after cleaning it up and there is no replacement configured
+const FALLBACK_NAME: &str = "Dehoisted";
+/// More combining characters on a single character than this is considered zalgo
+const MAX_COMBINING: usize = 2;
+
+/// The blocked words and patterns of a name policy, prepared once so checks don't have to
+pub struct NameFilters {
+    /// The words as configured, along with their folded form to match against
+    words: Vec<(String, String)>,
+    regexes: Vec<Regex>,
+}
+
+impl NameFilters {
+    pub fn compile(guild_id: &GuildId, config: &NamePolicy) -> Self {
+        let words = config
+            .words
+            .iter()
+            .filter(|word| !word.is_empty())
+            .map(|word| (word.clone(), fold_confusables(word)))
+            .collect();
+
+        let regexes = compile_regexes(guild_id, &config.regexes, "name");
+
+        NameFilters { words, regexes }
+    }
+}
+
+struct Violation {
+    rules: Vec<String>,
+    new_name: Option<String>,
+}
+
+/// Check the name a member shows up with against the name policy of the guild, renaming them if needed
+pub async fn check_name(guild_id: GuildId, user_id: UserId, context: Context) {
+    let info = match context.get_guild_info(&guild_id).await {
+        Ok(info) => info,
+        Err(e) => {
+            warn!("Failed to get the config for guild {} to check names: {}", guild_id, e);
+            return;
+        }
+    };
+    let config = &info.config.names;
+    if !config.enabled {
+        return;
+    }
+
+    let (guild, member) = match context.cache.get_guild(&guild_id) {
+        Some(guild) => match guild.get_member(&user_id) {
+            Some(member) => (guild, member),
+            None => return,
+        },
+        None => return,
+    };
+    let user = member.user();
+    // we can't rename the owner, and bots can pick whatever name they like
+    if user.bot || guild.owner == user_id || member.roles.iter().any(|role| config.exempt_roles.contains(role)) {
+        return;
+    }
+
+    let filters = match context.get_name_filters(&guild_id).await {
+        Ok(filters) => filters,
+        Err(e) => {
+            warn!("Failed to get the name filters for guild {}: {}", guild_id, e);
+            return;
+        }
+    };
+
+    let name = member.nickname.as_ref().unwrap_or(&user.name);
+    let violation = match evaluate(name, config, &filters) {
+        Some(violation) => violation,
+        None => return,
+    };
+
+    let mut entry = LogEntry::new(LogCategory::Members, "🏷️", "Name policy violation", COLOR_NEGATIVE)
+        .user(user_id, user.bot, &member.roles)
+        .field("User", name_and_id(&user, user_id))
+        .field("Name", name)
+        .field("Broken rules", violation.rules.join(", "));
+
+    entry = match violation.new_name {
+        // already the name they would get, happens when our own rename comes back in
+        Some(new_name) if new_name == *name => return,
+        Some(new_name) => {
+            let reason = format!("Name policy: {}", violation.rules.join(", "));
+            match rename(&guild_id, &user_id, &new_name, &reason, &context).await {
+                Ok(()) => entry.line(format!("✅ Changed nickname to `{}`", new_name)),
+                Err(e) => entry.line(format!("❌ Changing the nickname failed: {}", e.get_log_error())),
+            }
+        }
+        None => entry.line("No replacement nickname configured, the name was left as is"),
+    };
+
+    context.log(&guild_id, entry).await;
+}
+
+fn evaluate(name: &str, config: &NamePolicy, filters: &NameFilters) -> Option<Violation> {
+    let mut rules = Vec::new();
+    let mut cleaned = name.to_string();
+
+    if config.zalgo && is_zalgo(name) {
+        rules.push("Zalgo".to_string());
+        cleaned = strip_combining(&cleaned).trim().to_string();
+    }
+
+    if config.dehoist {
+        let dehoisted = cleaned.trim_start_matches(|c: char| c.is_whitespace() || config.hoist_characters.contains(c));
+        if dehoisted.len() != cleaned.len() {
+            rules.push("Hoisting".to_string());
+            cleaned = dehoisted.to_string();
+        }
+    }
+
+    // names with blocked content can't be cleaned up, those get replaced entirely
+    let folded = fold_confusables(name);
+    let mut blocked = false;
+    if let Some((word, _)) = filters
+        .words
+        .iter()
+        .find(|(_, folded_word)| folded.contains(folded_word.as_str()))
+    {
+        rules.push(format!("Blocked word `{}`", word));
+        blocked = true;
+    }
+    if let Some(regex) = filters
+        .regexes
+        .iter()
+        .find(|regex| regex.is_match(name) || regex.is_match(&folded))
+    {
+        rules.push(format!("Blocked pattern `{}`", regex.as_str()));
+        blocked = true;
+    }
+
+    if rules.is_empty() {
+        return None;
+    }
+
+    let new_name = if blocked {
+        config.replacement.clone()
+    } else if cleaned.is_empty() {
+        Some(config.replacement.clone().unwrap_or_else(|| FALLBACK_NAME.to_string()))
+    } else {
+        Some(cleaned)
+    };
+
+    Some(Violation {
+        rules,
+        new_name: new_name.map(|name| name.chars().take(MAX_NICKNAME_LENGTH).collect()),
+    })
+}
+
+fn is_zalgo(name: &str) -> bool {
+    let mut stacked = 0;
+    for c in name.chars() {
+        if is_combining(c) {
+            stacked += 1;
+            if stacked > MAX_COMBINING {
+                return true;
+            }
+        } else {
+            stacked = 0;
+        }
+    }
+    false
+}
+
+async fn rename(guild_id: &GuildId, user_id: &UserId, name: &str, reason: &str, context: &Context) -> GearResult<()> {
+    context
+        .api_client
+        .update_guild_member(*guild_id, *user_id)
+        .nick(Some(name))?
+        .reason(reason)?
+        .exec()
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> NamePolicy {
+        NamePolicy {
+            enabled: true,
+            zalgo: true,
+            words: vec!["discord".to_string()],
+            ..NamePolicy::default()
+        }
+    }
+
+    #[test]
+    fn detects_zalgo() {
+        assert!(!is_zalgo("René"));
+        assert!(!is_zalgo("e\u{0301}\u{0302}"));
+        assert!(is_zalgo("e\u{0301}\u{0302}\u{0303}"));
+        // marks only count when stacked on the same character
+        assert!(!is_zalgo("a\u{0301}\u{0302}b\u{0301}\u{0302}"));
+    }
+
+    #[test]
+    fn cleans_up_names() {
+        let config = policy();
+        let filters = NameFilters::compile(&GuildId::new(1), &config);
+
+        assert!(evaluate("normal", &config, &filters).is_none());
+
+        let violation = evaluate("!!!hoister", &config, &filters).unwrap();
+        assert_eq!(violation.rules, vec!["Hoisting"]);
+        assert_eq!(violation.new_name.as_deref(), Some("hoister"));
+
+        let violation = evaluate("z\u{0301}\u{0302}\u{0303}algo", &config, &filters).unwrap();
+        assert_eq!(violation.rules, vec!["Zalgo"]);
+        assert_eq!(violation.new_name.as_deref(), Some("zalgo"));
+
+        // nothing left after cleaning up
+        let violation = evaluate("!!!", &config, &filters).unwrap();
+        assert_eq!(violation.new_name.as_deref(), Some(FALLBACK_NAME));
+    }
+
+    #[test]
+    fn replaces_blocked_names() {
+        let mut config = policy();
+        let filters = NameFilters::compile(&GuildId::new(1), &config);
+
+        let violation = evaluate("Dіsc0rd staff", &config, &filters).unwrap();
+        assert_eq!(violation.rules, vec!["Blocked word `discord`"]);
+        assert_eq!(violation.new_name, None);
+
+        config.replacement = Some("Renamed".to_string());
+        let violation = evaluate("discord", &config, &filters).unwrap();
+        assert_eq!(violation.new_name.as_deref(), Some("Renamed"));
+    }
+}
@@ -0,0 +1,78 @@
+use twilight_model::channel::embed::Embed;
+use twilight_model::user::User as TwilightUser;
+
+use gearbot_2_lib::datastore::guild::{LogCategory, Phishing};
+use gearbot_2_lib::phishing::PhishingMatch;
+use gearbot_2_lib::util::markers::{ChannelId, GuildId, MessageId, RoleId};
+
+use crate::logging::{author_line, LogEntry, COLOR_NEGATIVE};
+use crate::moderation::punishment::punish;
+use crate::util::bot_context::Context;
+
+/// Scan the links in a message and its embeds, punishing the author if any of them lead to a scam.
+/// Returns if anything was found
+#[allow(clippy::too_many_arguments)]
+pub async fn check_message(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    author: &TwilightUser,
+    roles: &[RoleId],
+    content: &str,
+    embeds: &[Embed],
+    config: &Phishing,
+    context: &Context,
+) -> bool {
+    if author.bot || roles.iter().any(|role| config.exempt_roles.contains(role)) {
+        return false;
+    }
+
+    let found = match context
+        .phishing
+        .scan(content)
+        .or_else(|| embeds.iter().find_map(|embed| scan_embed(embed, context)))
+    {
+        Some(found) => found,
+        None => return false,
+    };
+
+    let reason = format!("Phishing: {}", found);
+    let outcomes = punish(
+        &guild_id,
+        &author.id,
+        &config.punishments,
+        &reason,
+        &[(channel_id, message_id)],
+        context,
+    )
+    .await;
+
+    let mut entry = LogEntry::new(LogCategory::Moderation, "🎣", "Phishing link detected", COLOR_NEGATIVE)
+        .user(author.id, author.bot, roles)
+        .channel(channel_id)
+        .field("User", author_line(author))
+        .field("Channel", format!("<#{}>", channel_id))
+        .field("Link", found.to_string());
+    for outcome in outcomes {
+        entry = entry.line(outcome);
+    }
+    context.log(&guild_id, entry).await;
+    true
+}
+
+// links in embeds don't have to show up in the content, the embed url and title are the usual hiding spots
+fn scan_embed(embed: &Embed, context: &Context) -> Option<PhishingMatch> {
+    let mut texts = vec![
+        embed.url.as_deref(),
+        embed.title.as_deref(),
+        embed.description.as_deref(),
+        embed.author.as_ref().and_then(|author| author.url.as_deref()),
+        embed.footer.as_ref().map(|footer| footer.text.as_str()),
+    ];
+    for field in &embed.fields {
+        texts.push(Some(&field.name));
+        texts.push(Some(&field.value));
+    }
+
+    texts.into_iter().flatten().find_map(|text| context.phishing.scan(text))
+}
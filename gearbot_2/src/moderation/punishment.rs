@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use twilight_http::request::AuditLogReason;
+use twilight_model::datetime::Timestamp;
+
+use gearbot_2_lib::datastore::guild::{GuildDatastore, InfractionType, Punishment};
+use gearbot_2_lib::util::markers::{ChannelId, GuildId, MessageId, UserId};
+use gearbot_2_lib::util::GearResult;
+
+use crate::moderation::escalation::record_infraction;
+use crate::util::bot_context::Context;
+
+/// Discord only allows bulk deleting up to 100 messages at a time
+const BULK_DELETE_LIMIT: usize = 100;
+
+/// Apply automatic punishments to a member.
+/// Returns a line per punishment describing the outcome so it can be included in the logs,
+/// one punishment failing (missing permissions for example) doesn't stop the others
+pub async fn punish(
+    guild_id: &GuildId,
+    user_id: &UserId,
+    punishments: &[Punishment],
+    reason: &str,
+    messages: &[(ChannelId, MessageId)],
+    context: &Context,
+) -> Vec<String> {
+    punish_escalated(guild_id, user_id, punishments, reason, messages, &[], context).await
+}
+
+/// Same as [`punish`], for punishments that are the result of escalations.
+/// The chain holds the escalation steps that led here so they don't keep triggering themselves
+pub async fn punish_escalated(
+    guild_id: &GuildId,
+    user_id: &UserId,
+    punishments: &[Punishment],
+    reason: &str,
+    messages: &[(ChannelId, MessageId)],
+    chain: &[String],
+    context: &Context,
+) -> Vec<String> {
+    let mut outcomes = Vec::with_capacity(punishments.len());
+    for punishment in punishments {
+        let (action, result) = match punishment {
+            Punishment::Delete => (
+                format!("Deleted {} message(s)", messages.len()),
+                delete_messages(messages, context).await,
+            ),
+            Punishment::Timeout { minutes } => (
+                format!("Timed out for {} minute(s)", minutes),
+                timeout(guild_id, user_id, *minutes, reason, chain, context).await,
+            ),
+            Punishment::Kick => (
+                "Kicked".to_string(),
+                kick(guild_id, user_id, reason, chain, context).await,
+            ),
+            Punishment::Ban => (
+                "Banned".to_string(),
+                ban(guild_id, user_id, reason, chain, context).await,
+            ),
+            Punishment::Warn => (
+                "Warned".to_string(),
+                record(guild_id, user_id, InfractionType::Warn, reason, chain, context).await,
+            ),
+        };
+
+        outcomes.push(match result {
+            Ok(()) => format!("✅ {}", action),
+            Err(e) => format!("❌ {} failed: {}", action, e.get_log_error()),
+        });
+    }
+    outcomes
+}
+
+/// Undo automatic punishments where possible, timeouts get lifted and bans removed.
+/// Deleted messages and kicks can't be undone so those are skipped
+pub async fn revert(
+    guild_id: &GuildId,
+    user_id: &UserId,
+    punishments: &[Punishment],
+    reason: &str,
+    context: &Context,
+) -> Vec<String> {
+    let mut outcomes = Vec::new();
+    for punishment in punishments {
+        let (action, result) = match punishment {
+            Punishment::Timeout { .. } => (
+                "Lifted timeout".to_string(),
+                lift_timeout(guild_id, user_id, reason, context).await,
+            ),
+            Punishment::Ban => ("Unbanned".to_string(), unban(guild_id, user_id, reason, context).await),
+            Punishment::Delete | Punishment::Kick | Punishment::Warn => continue,
+        };
+
+        outcomes.push(match result {
+            Ok(()) => format!("✅ {}", action),
+            Err(e) => format!("❌ {} failed: {}", action, e.get_log_error()),
+        });
+    }
+    outcomes
+}
+
+async fn delete_messages(messages: &[(ChannelId, MessageId)], context: &Context) -> GearResult<()> {
+    let mut per_channel: HashMap<ChannelId, Vec<MessageId>> = HashMap::new();
+    for (channel_id, message_id) in messages {
+        per_channel.entry(*channel_id).or_default().push(*message_id);
+    }
+
+    for (channel_id, message_ids) in per_channel {
+        for chunk in message_ids.chunks(BULK_DELETE_LIMIT) {
+            if chunk.len() == 1 {
+                context.api_client.delete_message(channel_id, chunk[0]).exec().await?;
+            } else {
+                context.api_client.delete_messages(channel_id, chunk).exec().await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn timeout(
+    guild_id: &GuildId,
+    user_id: &UserId,
+    minutes: u32,
+    reason: &str,
+    chain: &[String],
+    context: &Context,
+) -> GearResult<()> {
+    let until = Timestamp::from_secs(Utc::now().timestamp() + minutes as i64 * 60)?;
+    context
+        .api_client
+        .update_guild_member(*guild_id, *user_id)
+        .communication_disabled_until(Some(until))?
+        .reason(reason)?
+        .exec()
+        .await?;
+
+    record(guild_id, user_id, InfractionType::Timeout, reason, chain, context).await
+}
+
+async fn kick(
+    guild_id: &GuildId,
+    user_id: &UserId,
+    reason: &str,
+    chain: &[String],
+    context: &Context,
+) -> GearResult<()> {
+    context
+        .api_client
+        .remove_guild_member(*guild_id, *user_id)
+        .reason(reason)?
+        .exec()
+        .await?;
+
+    record(guild_id, user_id, InfractionType::Kick, reason, chain, context).await
+}
+
+async fn lift_timeout(guild_id: &GuildId, user_id: &UserId, reason: &str, context: &Context) -> GearResult<()> {
+    context
+        .api_client
+        .update_guild_member(*guild_id, *user_id)
+        .communication_disabled_until(None)?
+        .reason(reason)?
+        .exec()
+        .await?;
+
+    let info = context.get_guild_info(guild_id).await?;
+    GuildDatastore::new(&context.datastore, &info.encryption_key, guild_id)
+        .deactivate_infractions(user_id, InfractionType::Timeout)
+        .await?;
+    Ok(())
+}
+
+// the unban event updates the infractions
+async fn unban(guild_id: &GuildId, user_id: &UserId, reason: &str, context: &Context) -> GearResult<()> {
+    context
+        .api_client
+        .delete_ban(*guild_id, *user_id)
+        .reason(reason)?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+// the ban event skips bans we issue, these are recorded here so they keep their escalation chain
+async fn ban(
+    guild_id: &GuildId,
+    user_id: &UserId,
+    reason: &str,
+    chain: &[String],
+    context: &Context,
+) -> GearResult<()> {
+    let request = context
+        .api_client
+        .create_ban(*guild_id, *user_id)
+        .delete_message_days(1)?
+        .reason(reason)?;
+
+    // the event can come in before the request returns
+    context.issue_ban(guild_id, user_id);
+    if let Err(e) = request.exec().await {
+        context.take_issued_ban(guild_id, user_id);
+        return Err(e.into());
+    }
+
+    record(guild_id, user_id, InfractionType::Ban, reason, chain, context).await
+}
+
+async fn record(
+    guild_id: &GuildId,
+    user_id: &UserId,
+    kind: InfractionType,
+    reason: &str,
+    chain: &[String],
+    context: &Context,
+) -> GearResult<()> {
+    record_infraction(
+        guild_id,
+        user_id,
+        Some(&context.bot_id.cast()),
+        kind,
+        Some(reason),
+        chain,
+        context,
+    )
+    .await
+}
+
+/// Short description of a punishment, used to explain escalations
+pub fn describe_punishment(punishment: &Punishment) -> String {
+    match punishment {
+        Punishment::Delete => "message removal".to_string(),
+        Punishment::Timeout { minutes } => format!("{} minute timeout", minutes),
+        Punishment::Kick => "kick".to_string(),
+        Punishment::Ban => "ban".to_string(),
+        Punishment::Warn => "warning".to_string(),
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use tracing::warn;
+use twilight_http::request::AuditLogReason;
+use twilight_model::guild::VerificationLevel;
+
+use gearbot_2_lib::datastore::guild::{LogCategory, Punishment, RaidProtection};
+use gearbot_2_lib::util::markers::{GuildId, UserId};
+use gearbot_2_lib::util::snowflake_timestamp;
+use gearbot_2_lib::util::GearResult;
+
+use crate::cache::User;
+use crate::logging::{name_and_id, LogEntry, COLOR_ALERT, COLOR_NEGATIVE, COLOR_POSITIVE};
+use crate::moderation::punishment::{punish, revert};
+use crate::util::bot_context::Context;
+
+/// Joins older than this are dropped, no matter what the windows are configured to
+const MAX_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+const RAID_REASON: &str = "Raid protection: joined during a raid";
+const RAID_END_REASON: &str = "Raid protection: raid ended";
+
+struct Join {
+    at: Instant,
+    user_id: UserId,
+}
+
+/// An ongoing raid, these only live in memory on the cluster handling the guild
+pub struct Raid {
+    pub started: DateTime<Utc>,
+    /// Verification level from before the raid, only set if we changed it
+    pub previous_verification: Option<VerificationLevel>,
+    /// What was done to the raiders, kept so the same thing gets undone even if the config changes mid-raid
+    pub punishments: Vec<Punishment>,
+    pub members: Vec<UserId>,
+}
+
+#[derive(Default)]
+struct GuildJoins {
+    joins: VecDeque<Join>,
+    raid: Option<Raid>,
+}
+
+pub enum JoinOutcome {
+    Nothing,
+    /// This join pushed a window over its limit, contains everyone in that window
+    RaidStarted(Vec<UserId>),
+    /// Joined while the guild was already in raid mode
+    Raider(Vec<Punishment>),
+}
+
+/// Recent suspicious joins per guild and the raids they triggered
+#[derive(Default)]
+pub struct RaidTracker {
+    guilds: Mutex<HashMap<GuildId, GuildJoins>>,
+}
+
+impl RaidTracker {
+    /// Register a suspicious join, starting raid mode if it pushes any window over its limit
+    pub fn register(&self, guild_id: GuildId, user_id: UserId, config: &RaidProtection) -> JoinOutcome {
+        let now = Instant::now();
+        let mut guilds = self.guilds.lock();
+        let guild = guilds.entry(guild_id).or_default();
+
+        if let Some(raid) = &mut guild.raid {
+            if !raid.members.contains(&user_id) {
+                raid.members.push(user_id);
+            }
+            return JoinOutcome::Raider(raid.punishments.clone());
+        }
+
+        while matches!(guild.joins.front(), Some(join) if now.duration_since(join.at) > MAX_WINDOW) {
+            guild.joins.pop_front();
+        }
+        guild.joins.push_back(Join { at: now, user_id });
+
+        for window in &config.windows {
+            let duration = Duration::from_secs(window.seconds as u64);
+            let mut members: Vec<UserId> = guild
+                .joins
+                .iter()
+                .filter(|join| now.duration_since(join.at) <= duration)
+                .map(|join| join.user_id)
+                .collect();
+            // people leaving and joining again only count once
+            members.sort_unstable();
+            members.dedup();
+
+            if members.len() as u32 > window.joins {
+                guild.joins.clear();
+                guild.raid = Some(Raid {
+                    started: Utc::now(),
+                    previous_verification: None,
+                    punishments: config.punishments.clone(),
+                    members: members.clone(),
+                });
+                return JoinOutcome::RaidStarted(members);
+            }
+        }
+
+        JoinOutcome::Nothing
+    }
+
+    fn set_previous_verification(&self, guild_id: &GuildId, level: VerificationLevel) {
+        if let Some(raid) = self
+            .guilds
+            .lock()
+            .get_mut(guild_id)
+            .and_then(|guild| guild.raid.as_mut())
+        {
+            raid.previous_verification = Some(level);
+        }
+    }
+
+    /// Take the raid out of the tracker so it can be wrapped up
+    pub fn end(&self, guild_id: &GuildId) -> Option<Raid> {
+        self.guilds.lock().get_mut(guild_id)?.raid.take()
+    }
+
+    /// Drop guilds that had no suspicious joins in a while, ongoing raids are kept until someone ends them
+    pub fn cleanup(&self) {
+        let now = Instant::now();
+        self.guilds.lock().retain(|_, guild| {
+            guild.joins.retain(|join| now.duration_since(join.at) < MAX_WINDOW);
+            guild.raid.is_some() || !guild.joins.is_empty()
+        });
+    }
+}
+
+/// Check a new member against the raid protection of the guild
+pub async fn check_join(guild_id: GuildId, user_id: UserId, user: Arc<User>, context: Context) {
+    if user.bot {
+        return;
+    }
+
+    let info = match context.get_guild_info(&guild_id).await {
+        Ok(info) => info,
+        Err(e) => {
+            warn!(
+                "Failed to get the config for guild {} to check for raids: {}",
+                guild_id, e
+            );
+            return;
+        }
+    };
+    let config = &info.config.raid_protection;
+    if !config.enabled {
+        return;
+    }
+
+    let account_age = Utc::now()
+        .signed_duration_since(snowflake_timestamp(&user_id))
+        .num_hours()
+        .max(0) as u64;
+    if !config.is_suspicious(account_age, user.avatar.is_some()) {
+        return;
+    }
+
+    match context.raid_tracker.register(guild_id, user_id, config) {
+        JoinOutcome::Nothing => {}
+        JoinOutcome::Raider(punishments) => {
+            let outcomes = punish(&guild_id, &user_id, &punishments, RAID_REASON, &[], &context).await;
+            let mut entry = LogEntry::new(LogCategory::Moderation, "🚫", "Raider joined", COLOR_NEGATIVE)
+                .user(user_id, user.bot, &[])
+                .field("User", name_and_id(&user, user_id));
+            for outcome in outcomes {
+                entry = entry.line(outcome);
+            }
+            context.log(&guild_id, entry).await;
+        }
+        JoinOutcome::RaidStarted(members) => start_raid(guild_id, members, config, &context).await,
+    }
+}
+
+async fn start_raid(guild_id: GuildId, members: Vec<UserId>, config: &RaidProtection, context: &Context) {
+    let mut entry = LogEntry::new(LogCategory::Moderation, "🚨", "Raid detected", COLOR_ALERT)
+        .alert("Raid mode enabled")
+        .field("Suspicious joins", members.len().to_string());
+
+    if let (Some(level), Some(guild)) = (config.verification_level, context.cache.get_guild(&guild_id)) {
+        let current = guild.verification_level;
+        if (current as u8) < (level as u8) {
+            match raise_verification(&guild_id, level, context).await {
+                Ok(()) => {
+                    context.raid_tracker.set_previous_verification(&guild_id, current);
+                    entry = entry.line(format!("✅ Raised the verification level to {:?}", level));
+                }
+                Err(e) => {
+                    entry = entry.line(format!(
+                        "❌ Raising the verification level failed: {}",
+                        e.get_log_error()
+                    ))
+                }
+            }
+        }
+    }
+
+    let mut failures = 0;
+    for user_id in &members {
+        let outcomes = punish(&guild_id, user_id, &config.punishments, RAID_REASON, &[], context).await;
+        if outcomes.iter().any(|outcome| outcome.starts_with('❌')) {
+            failures += 1;
+        }
+    }
+    if !config.punishments.is_empty() {
+        entry = entry.line(format!(
+            "Punished {} of {} account(s), everyone matching that joins during the raid will be punished as well",
+            members.len() - failures,
+            members.len()
+        ));
+    }
+
+    context.log(&guild_id, entry).await;
+}
+
+async fn raise_verification(guild_id: &GuildId, level: VerificationLevel, context: &Context) -> GearResult<()> {
+    context
+        .api_client
+        .update_guild(*guild_id)
+        .verification_level(Some(level))
+        .reason(RAID_REASON)?
+        .exec()
+        .await?;
+    Ok(())
+}
+
+/// End raid mode: restore the verification level and undo the punishments where possible.
+/// Returns the raid so the accounts involved can be reported back, nothing if there was no raid going on.
+/// Anything failing along the way ends up in the logs, the raid is over either way
+pub async fn end_raid(guild_id: &GuildId, context: &Context) -> Option<Raid> {
+    let raid = context.raid_tracker.end(guild_id)?;
+
+    let mut entry = LogEntry::new(LogCategory::Moderation, "🛡️", "Raid ended", COLOR_POSITIVE)
+        .field("Accounts involved", raid.members.len().to_string())
+        .field(
+            "Duration",
+            format!(
+                "{} minute(s)",
+                Utc::now().signed_duration_since(raid.started).num_minutes()
+            ),
+        );
+
+    // the raid is already over at this point, a failure here shouldn't stop the punishments from being undone
+    if let Some(level) = raid.previous_verification {
+        entry = match restore_verification(guild_id, level, context).await {
+            Ok(()) => entry.line(format!("✅ Restored the verification level to {:?}", level)),
+            Err(e) => entry.line(format!(
+                "❌ Restoring the verification level to {:?} failed: {}",
+                level,
+                e.get_log_error()
+            )),
+        };
+    }
+
+    let mut failures = 0;
+    for user_id in &raid.members {
+        let outcomes = revert(guild_id, user_id, &raid.punishments, RAID_END_REASON, context).await;
+        if outcomes.iter().any(|outcome| outcome.starts_with('❌')) {
+            failures += 1;
+        }
+    }
+    if failures > 0 {
+        entry = entry.line(format!("❌ Failed to undo the punishments for {} account(s)", failures));
+    }
+
+    context.log(guild_id, entry).await;
+    Some(raid)
+}
+
+async fn restore_verification(guild_id: &GuildId, level: VerificationLevel, context: &Context) -> GearResult<()> {
+    context
+        .api_client
+        .update_guild(*guild_id)
+        .verification_level(Some(level))
+        .reason(RAID_END_REASON)?
+        .exec()
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use gearbot_2_lib::datastore::guild::JoinWindow;
+
+    use super::*;
+
+    fn config(windows: &[(u32, u32)]) -> RaidProtection {
+        RaidProtection {
+            enabled: true,
+            windows: windows
+                .iter()
+                .map(|(joins, seconds)| JoinWindow {
+                    joins: *joins,
+                    seconds: *seconds,
+                })
+                .collect(),
+            ..RaidProtection::default()
+        }
+    }
+
+    #[test]
+    fn starts_a_raid_over_the_limit() {
+        let tracker = RaidTracker::default();
+        let config = config(&[(2, 60)]);
+        let guild_id = GuildId::new(1);
+
+        assert!(matches!(
+            tracker.register(guild_id, UserId::new(1), &config),
+            JoinOutcome::Nothing
+        ));
+        assert!(matches!(
+            tracker.register(guild_id, UserId::new(2), &config),
+            JoinOutcome::Nothing
+        ));
+        match tracker.register(guild_id, UserId::new(3), &config) {
+            JoinOutcome::RaidStarted(members) => {
+                assert_eq!(members, vec![UserId::new(1), UserId::new(2), UserId::new(3)])
+            }
+            _ => panic!("the third join should have started a raid"),
+        }
+
+        // everyone after that is a raider until the raid ends
+        match tracker.register(guild_id, UserId::new(4), &config) {
+            JoinOutcome::Raider(punishments) => assert_eq!(punishments, config.punishments),
+            _ => panic!("joins during a raid are raiders"),
+        }
+        let raid = tracker.end(&guild_id).unwrap();
+        assert_eq!(raid.members.len(), 4);
+        assert!(tracker.end(&guild_id).is_none());
+        assert!(matches!(
+            tracker.register(guild_id, UserId::new(5), &config),
+            JoinOutcome::Nothing
+        ));
+    }
+
+    #[test]
+    fn rejoins_count_once() {
+        let tracker = RaidTracker::default();
+        let config = config(&[(1, 60)]);
+        let guild_id = GuildId::new(1);
+
+        for _ in 0..3 {
+            assert!(matches!(
+                tracker.register(guild_id, UserId::new(1), &config),
+                JoinOutcome::Nothing
+            ));
+        }
+        assert!(matches!(
+            tracker.register(guild_id, UserId::new(2), &config),
+            JoinOutcome::RaidStarted(_)
+        ));
+    }
+
+    #[test]
+    fn guilds_are_separate() {
+        let tracker = RaidTracker::default();
+        let config = config(&[(1, 60)]);
+
+        assert!(matches!(
+            tracker.register(GuildId::new(1), UserId::new(1), &config),
+            JoinOutcome::Nothing
+        ));
+        assert!(matches!(
+            tracker.register(GuildId::new(2), UserId::new(2), &config),
+            JoinOutcome::Nothing
+        ));
+        assert!(tracker.end(&GuildId::new(1)).is_none());
+    }
+
+    #[test]
+    fn keeps_raids_during_cleanup() {
+        let tracker = RaidTracker::default();
+        let config = config(&[(0, 60)]);
+        let guild_id = GuildId::new(1);
+
+        assert!(matches!(
+            tracker.register(guild_id, UserId::new(1), &config),
+            JoinOutcome::RaidStarted(_)
+        ));
+        tracker.set_previous_verification(&guild_id, VerificationLevel::Low);
+        tracker.cleanup();
+        let raid = tracker.end(&guild_id).unwrap();
+        assert_eq!(raid.previous_verification, Some(VerificationLevel::Low));
+    }
+}
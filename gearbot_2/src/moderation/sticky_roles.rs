@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use twilight_http::request::AuditLogReason;
+
+use gearbot_2_lib::datastore::guild::{GuildDatastore, LogCategory};
+use gearbot_2_lib::util::markers::{GuildId, RoleId, UserId};
+use gearbot_2_lib::util::GearResult;
+
+use crate::cache::Member;
+use crate::logging::{name_and_id, role_names, LogEntry, COLOR_POSITIVE};
+use crate::util::bot_context::Context;
+
+const RESTORE_REASON: &str = "Sticky roles: member rejoined";
+
+/// Remember the roles and nickname of a member that left, if the guild wants to give those back on return
+pub async fn store_member(guild_id: GuildId, user_id: UserId, member: Arc<Member>, context: Context) -> GearResult<()> {
+    let info = context.get_guild_info(&guild_id).await?;
+    let config = &info.config.sticky_roles;
+    if !config.enabled || (member.roles.is_empty() && member.nickname.is_none()) {
+        return Ok(());
+    }
+
+    // everything is stored, what gets restored is decided by the config at the time they return
+    GuildDatastore::new(&context.datastore, &info.encryption_key, &guild_id)
+        .store_sticky_member(&user_id, &member.roles, member.nickname.as_deref())
+        .await?;
+
+    Ok(())
+}
+
+/// Give a returning member back what they had when they left, if they weren't gone for too long
+pub async fn restore_member(guild_id: GuildId, user_id: UserId, context: Context) -> GearResult<()> {
+    let info = context.get_guild_info(&guild_id).await?;
+    let config = &info.config.sticky_roles;
+    if !config.enabled {
+        return Ok(());
+    }
+
+    let sticky = match GuildDatastore::new(&context.datastore, &info.encryption_key, &guild_id)
+        .take_sticky_member(&user_id, config.hours)
+        .await?
+    {
+        Some(sticky) => sticky,
+        None => return Ok(()),
+    };
+
+    let guild = match context.cache.get_guild(&guild_id) {
+        Some(guild) => guild,
+        None => return Ok(()),
+    };
+    let member = match guild.get_member(&user_id) {
+        Some(member) => member,
+        // already gone again
+        None => return Ok(()),
+    };
+
+    // managed roles belong to integrations and boosting, discord hands those out on its own
+    let restored = sticky
+        .roles
+        .into_iter()
+        .filter(|role| config.restores(role))
+        .filter(|role| matches!(guild.get_role(role), Some(role) if !role.managed))
+        .filter(|role| !member.roles.contains(role))
+        .collect::<Vec<_>>();
+    let nickname = sticky.nickname.filter(|_| config.nickname);
+    if restored.is_empty() && nickname.is_none() {
+        return Ok(());
+    }
+
+    let user = member.user();
+    let mut entry = LogEntry::new(LogCategory::Members, "♻️", "Member restored", COLOR_POSITIVE)
+        .user(user_id, user.bot, &member.roles)
+        .field("User", name_and_id(&user, user_id));
+    if !restored.is_empty() {
+        entry = entry.field("Roles", role_names(&guild, &restored));
+    }
+    if let Some(nickname) = &nickname {
+        entry = entry.field("Nickname", nickname);
+    }
+
+    // roles are added one at a time instead of setting the full list, so roles handed out
+    // at the same time (auto roles, verification) don't get overwritten
+    let mut failures = Vec::new();
+    for role_id in &restored {
+        if let Err(e) = add_role(&guild_id, &user_id, role_id, &context).await {
+            failures.push(format!("{}: {}", role_names(&guild, &[*role_id]), e.get_log_error()));
+        }
+    }
+    if let Some(nickname) = &nickname {
+        if let Err(e) = set_nickname(&guild_id, &user_id, nickname, &context).await {
+            failures.push(format!("Nickname: {}", e.get_log_error()));
+        }
+    }
+
+    if failures.is_empty() {
+        entry = entry.line("✅ Gave back what they had before leaving");
+    } else {
+        for failure in failures {
+            entry = entry.line(format!("❌ Restoring failed for {}", failure));
+        }
+    }
+
+    context.log(&guild_id, entry).await;
+    Ok(())
+}
+
+async fn add_role(guild_id: &GuildId, user_id: &UserId, role_id: &RoleId, context: &Context) -> GearResult<()> {
+    context
+        .api_client
+        .add_guild_member_role(*guild_id, *user_id, *role_id)
+        .reason(RESTORE_REASON)?
+        .exec()
+        .await?;
+    Ok(())
+}
+
+async fn set_nickname(guild_id: &GuildId, user_id: &UserId, nickname: &str, context: &Context) -> GearResult<()> {
+    context
+        .api_client
+        .update_guild_member(*guild_id, *user_id)
+        .nick(Some(nickname))?
+        .reason(RESTORE_REASON)?
+        .exec()
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+use twilight_http::request::AuditLogReason;
+use twilight_model::application::component::button::ButtonStyle;
+use twilight_model::application::component::{ActionRow, Button, Component};
+
+use gearbot_2_lib::datastore::guild::{LogCategory, Verification};
+use gearbot_2_lib::util::custom_id::{self, CustomId};
+use gearbot_2_lib::util::markers::{ChannelId, GuildId, RoleId, UserId};
+use gearbot_2_lib::util::snowflake_timestamp;
+use gearbot_2_lib::util::GearResult;
+
+use crate::logging::{name_and_id, LogEntry, COLOR_NEGATIVE, COLOR_POSITIVE};
+use crate::util::bot_context::Context;
+
+/// How long someone has to answer a challenge
+const CHALLENGE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const CHALLENGE_OPTIONS: usize = 4;
+/// What members get to pick from, easy to tell apart for people but not for a script reading the labels
+const CHALLENGE_ANIMALS: [(&str, &str); 8] = [
+    ("🐶", "dog"),
+    ("🐱", "cat"),
+    ("🐸", "frog"),
+    ("🦊", "fox"),
+    ("🐼", "panda"),
+    ("🐧", "penguin"),
+    ("🐢", "turtle"),
+    ("🦉", "owl"),
+];
+
+const VERIFY_REASON: &str = "Verification: passed";
+
+struct Challenge {
+    issued: Instant,
+    answer: u8,
+}
+
+/// Challenges handed out and not yet answered, per guild and member
+#[derive(Default)]
+pub struct VerificationTracker {
+    challenges: Mutex<HashMap<(GuildId, UserId), Challenge>>,
+}
+
+impl VerificationTracker {
+    fn issue(&self, guild_id: GuildId, user_id: UserId, answer: u8) {
+        self.challenges.lock().insert(
+            (guild_id, user_id),
+            Challenge {
+                issued: Instant::now(),
+                answer,
+            },
+        );
+    }
+
+    /// Take the challenge out, every challenge only gets one answer. Nothing if it expired
+    fn take(&self, guild_id: GuildId, user_id: UserId) -> Option<u8> {
+        self.challenges
+            .lock()
+            .remove(&(guild_id, user_id))
+            .filter(|challenge| challenge.issued.elapsed() < CHALLENGE_TIMEOUT)
+            .map(|challenge| challenge.answer)
+    }
+
+    /// Drop challenges nobody answered in time
+    pub fn cleanup(&self) {
+        self.challenges
+            .lock()
+            .retain(|_, challenge| challenge.issued.elapsed() < CHALLENGE_TIMEOUT);
+    }
+}
+
+pub enum VerifyOutcome {
+    NotConfigured,
+    AlreadyVerified,
+    AccountTooNew {
+        hours: u64,
+    },
+    /// Pick the animal out of the buttons
+    Challenge {
+        animal: &'static str,
+        components: Vec<Component>,
+    },
+    WrongAnswer,
+    Expired,
+    Verified,
+    Failed,
+}
+
+/// The panel for the config as it is right now, the role is looked up again when someone clicks it
+fn panel_components(config: &Verification) -> Vec<Component> {
+    vec![Component::ActionRow(ActionRow {
+        components: vec![Component::Button(Button {
+            custom_id: Some(CustomId::new(custom_id::VERIFY).encode()),
+            disabled: false,
+            emoji: None,
+            label: Some(config.button_label.clone()),
+            style: ButtonStyle::Success,
+            url: None,
+        })],
+    })]
+}
+
+/// Post a verification panel in a channel, false if verification isn't set up so there is nothing to post
+pub async fn post_panel(guild_id: &GuildId, channel_id: &ChannelId, context: &Context) -> GearResult<bool> {
+    let info = context.get_guild_info(guild_id).await?;
+    let config = &info.config.verification;
+    if !config.enabled || config.role.is_none() {
+        return Ok(false);
+    }
+
+    context
+        .api_client
+        .create_message(*channel_id)
+        .content(&config.message)?
+        .components(&panel_components(config))?
+        .exec()
+        .await?;
+
+    Ok(true)
+}
+
+/// Handle a click on the verify button, or on one of the answers of the challenge if it comes with one
+pub async fn verify(
+    guild_id: GuildId,
+    user_id: UserId,
+    answer: Option<u8>,
+    context: &Context,
+) -> GearResult<VerifyOutcome> {
+    let info = context.get_guild_info(&guild_id).await?;
+    let config = &info.config.verification;
+    let role_id = match config.role {
+        Some(role_id) if config.enabled => role_id,
+        _ => return Ok(VerifyOutcome::NotConfigured),
+    };
+
+    let member = match context.cache.get_guild_member(&guild_id, &user_id) {
+        Some(member) => member,
+        // can't really click anything without being in the server, but the cache might be catching up
+        None => return Ok(VerifyOutcome::Failed),
+    };
+    if member.roles.contains(&role_id) {
+        return Ok(VerifyOutcome::AlreadyVerified);
+    }
+    let user = member.user();
+    let entry = || {
+        LogEntry::new(LogCategory::Members, "🛂", "Verification failed", COLOR_NEGATIVE)
+            .user(user_id, user.bot, &member.roles)
+            .field("User", name_and_id(&user, user_id))
+    };
+
+    if let Some(min_age) = config.min_account_age_hours {
+        let age = Utc::now()
+            .signed_duration_since(snowflake_timestamp(&user_id))
+            .num_hours()
+            .max(0) as u64;
+        if age < min_age {
+            context
+                .log(
+                    &guild_id,
+                    entry().line(format!(
+                        "❌ Account is {} hour(s) old, it needs to be at least {} hour(s) old",
+                        age, min_age
+                    )),
+                )
+                .await;
+            return Ok(VerifyOutcome::AccountTooNew { hours: min_age });
+        }
+    }
+
+    if config.captcha {
+        match answer {
+            None => return Ok(challenge(guild_id, user_id, context)),
+            Some(answer) => match context.verification.take(guild_id, user_id) {
+                Some(expected) if expected == answer => {}
+                Some(_) => {
+                    context
+                        .log(&guild_id, entry().line("❌ Picked the wrong answer to the challenge"))
+                        .await;
+                    return Ok(VerifyOutcome::WrongAnswer);
+                }
+                None => return Ok(VerifyOutcome::Expired),
+            },
+        }
+    }
+
+    match grant_role(&guild_id, &user_id, &role_id, context).await {
+        Ok(()) => {
+            let entry = LogEntry::new(LogCategory::Members, "🛂", "Member verified", COLOR_POSITIVE)
+                .user(user_id, user.bot, &member.roles)
+                .field("User", name_and_id(&user, user_id));
+            context.log(&guild_id, entry).await;
+            Ok(VerifyOutcome::Verified)
+        }
+        Err(e) => {
+            context
+                .log(
+                    &guild_id,
+                    entry().line(format!(
+                        "❌ Handing out the verified role failed: {}",
+                        e.get_log_error()
+                    )),
+                )
+                .await;
+            Ok(VerifyOutcome::Failed)
+        }
+    }
+}
+
+fn challenge(guild_id: GuildId, user_id: UserId, context: &Context) -> VerifyOutcome {
+    let mut rng = thread_rng();
+    let options = CHALLENGE_ANIMALS
+        .choose_multiple(&mut rng, CHALLENGE_OPTIONS)
+        .collect::<Vec<_>>();
+    let answer = rng.gen_range(0..options.len()) as u8;
+    context.verification.issue(guild_id, user_id, answer);
+
+    let buttons = options
+        .iter()
+        .enumerate()
+        .map(|(i, (emoji, _))| {
+            Component::Button(Button {
+                custom_id: Some(CustomId::new(custom_id::VERIFY).arg(i).encode()),
+                disabled: false,
+                emoji: None,
+                label: Some(emoji.to_string()),
+                style: ButtonStyle::Secondary,
+                url: None,
+            })
+        })
+        .collect();
+
+    VerifyOutcome::Challenge {
+        animal: options[answer as usize].1,
+        components: vec![Component::ActionRow(ActionRow { components: buttons })],
+    }
+}
+
+async fn grant_role(guild_id: &GuildId, user_id: &UserId, role_id: &RoleId, context: &Context) -> GearResult<()> {
+    context
+        .api_client
+        .add_guild_member_role(*guild_id, *user_id, *role_id)
+        .reason(VERIFY_REASON)?
+        .exec()
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::time::Instant;
+
+use chrono::Utc;
+use tokio::time::sleep;
+use tracing::debug;
+use twilight_model::guild::audit_log::AuditLogEventType;
+
+use gearbot_2_lib::datastore::guild::LogCategory;
+use gearbot_2_lib::util::markers::{GenericId, GuildId};
+use gearbot_2_lib::util::snowflake_timestamp;
+
+use crate::cache::User;
+use crate::logging::audit::{Attribution, FetchedAuditLog, AUDIT_LOG_DELAY, AUDIT_LOG_WINDOW};
+use crate::BotContext;
+
+impl BotContext {
+    /// Same as [`BotContext::attribute`], but skips the lookup if the guild doesn't log this category anyways
+    pub async fn attribute_log(
+        &self,
+        guild_id: &GuildId,
+        category: LogCategory,
+        kinds: &[AuditLogEventType],
+        target: GenericId,
+    ) -> Option<Attribution> {
+        if !self.logs_category(guild_id, category).await {
+            return None;
+        }
+        self.attribute(guild_id, kinds, target).await
+    }
+
+    /// Same as [`BotContext::attribute_log`], but also does the lookup when the guild escalates punishments,
+    /// punishments done by hand count towards those as well
+    pub async fn attribute_moderation(
+        &self,
+        guild_id: &GuildId,
+        category: LogCategory,
+        kinds: &[AuditLogEventType],
+        target: GenericId,
+    ) -> Option<Attribution> {
+        let escalates = matches!(self.get_guild_info(guild_id).await, Ok(info) if info.config.escalation.enabled);
+        if escalates {
+            self.attribute(guild_id, kinds, target).await
+        } else {
+            self.attribute_log(guild_id, category, kinds, target).await
+        }
+    }
+
+    /// Find out who performed an action on a target by looking at the audit log.
+    /// Returns nothing if there is no matching entry or we are not allowed to see the audit log
+    pub async fn attribute(
+        &self,
+        guild_id: &GuildId,
+        kinds: &[AuditLogEventType],
+        target: GenericId,
+    ) -> Option<Attribution> {
+        let received = Instant::now();
+        sleep(AUDIT_LOG_DELAY).await;
+
+        let slot = self.audit_logs.slot(guild_id);
+        let mut fetched = slot.lock().await;
+
+        // only reuse logs fetched after this event came in, older ones can't contain it yet
+        if !matches!(&*fetched, Some(log) if log.fetched_at > received) {
+            match self.api_client.audit_log(*guild_id).exec().await {
+                Ok(response) => match response.model().await {
+                    Ok(log) => {
+                        *fetched = Some(FetchedAuditLog {
+                            fetched_at: Instant::now(),
+                            log,
+                        })
+                    }
+                    Err(e) => {
+                        debug!("Failed to deserialize the audit log for guild {}: {:?}", guild_id, e);
+                        return None;
+                    }
+                },
+                Err(e) => {
+                    // most likely missing permissions to view the audit log
+                    debug!("Failed to fetch the audit log for guild {}: {}", guild_id, e);
+                    return None;
+                }
+            }
+        }
+
+        let log = &fetched.as_ref()?.log;
+        let now = Utc::now();
+        let entry = log.entries.iter().find(|entry| {
+            kinds.contains(&entry.action_type)
+                && entry.target_id == Some(target)
+                && now
+                    .signed_duration_since(snowflake_timestamp(&entry.id))
+                    .to_std()
+                    .map_or(true, |age| age < AUDIT_LOG_WINDOW)
+        })?;
+
+        let moderator = entry.user_id?;
+        let moderator_name = log
+            .users
+            .iter()
+            .find(|user| user.id == moderator)
+            .map(|user| User::assemble(user.clone(), None).to_string())
+            .or_else(|| self.cache.get_user(&moderator).map(|user| user.to_string()))
+            .unwrap_or_else(|| "Unknown user".to_string());
+
+        Some(Attribution {
+            moderator,
+            moderator_name,
+            reason: entry.reason.clone(),
+        })
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use tracing::warn;
+use twilight_http::error::ErrorType;
+
+use gearbot_2_lib::util::markers::{GuildId, UserId};
+
+use crate::BotContext;
+
+impl BotContext {
+    /// Check if a user is banned from a guild, loading the ban list of that guild if we don't have it yet.
+    /// Returns none if we can't tell, failing to get the list shouldn't take down whatever wanted to know
+    pub async fn is_banned(&self, guild_id: &GuildId, user_id: &UserId) -> Option<bool> {
+        let guild = self.cache.get_guild(guild_id)?;
+        if let Some(banned) = guild.is_banned(user_id) {
+            return Some(banned);
+        }
+
+        let response = match self.api_client.bans(*guild_id).exec().await {
+            Ok(response) => response,
+            Err(e) => {
+                // without ban permissions we simply can't tell, anything else is worth knowing about
+                if !matches!(e.kind(), ErrorType::Response { status, .. } if *status == 403) {
+                    warn!("Failed to fetch the bans of guild {}: {}", guild_id, e);
+                }
+                return None;
+            }
+        };
+        let bans = match response.models().await {
+            Ok(bans) => bans.into_iter().map(|ban| ban.user.id).collect::<HashSet<UserId>>(),
+            Err(e) => {
+                warn!("Failed to read the bans of guild {}: {:?}", guild_id, e);
+                return None;
+            }
+        };
+        let banned = bans.contains(user_id);
+        guild.set_bans(bans);
+
+        Some(banned)
+    }
+
+    /// Remember a ban we are about to issue ourselves, so the ban event knows it's already taken care of
+    pub fn issue_ban(&self, guild_id: &GuildId, user_id: &UserId) {
+        self.issued_bans.lock().insert((*guild_id, *user_id));
+    }
+
+    /// Was this ban issued by us? Only says yes once per ban
+    pub fn take_issued_ban(&self, guild_id: &GuildId, user_id: &UserId) -> bool {
+        self.issued_bans.lock().remove(&(*guild_id, *user_id))
+    }
+}
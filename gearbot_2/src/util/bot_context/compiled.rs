@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use gearbot_2_lib::datastore::guild::GuildConfig;
+use gearbot_2_lib::datastore::DatastoreResult;
+use gearbot_2_lib::util::markers::GuildId;
+
+use crate::moderation::censor::CensorFilters;
+use crate::moderation::names::NameFilters;
+use crate::util::bot_context::{BotContext, CompiledCache};
+
+impl BotContext {
+    /// Get the compiled censor filters for a guild
+    pub async fn get_censor_filters(&self, guild_id: &GuildId) -> DatastoreResult<Arc<CensorFilters>> {
+        self.get_compiled(guild_id, &self.censor_filters, |config| {
+            CensorFilters::compile(guild_id, &config.censoring)
+        })
+        .await
+    }
+
+    /// Get the compiled name filters for a guild
+    pub async fn get_name_filters(&self, guild_id: &GuildId) -> DatastoreResult<Arc<NameFilters>> {
+        self.get_compiled(guild_id, &self.name_filters, |config| {
+            NameFilters::compile(guild_id, &config.names)
+        })
+        .await
+    }
+
+    /// Get something compiled from the config of a guild, this gets recompiled whenever the guild info changes
+    async fn get_compiled<T>(
+        &self,
+        guild_id: &GuildId,
+        cache: &CompiledCache<T>,
+        compile: impl FnOnce(&GuildConfig) -> T,
+    ) -> DatastoreResult<Arc<T>> {
+        let info = self.get_guild_info(guild_id).await?;
+        if let Some((compiled_from, compiled)) = cache.read().get(guild_id) {
+            if Arc::ptr_eq(compiled_from, &info) {
+                return Ok(compiled.clone());
+            }
+        }
+
+        let compiled = Arc::new(compile(&info.config));
+        cache.write().insert(*guild_id, (info, compiled.clone()));
+        Ok(compiled)
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::Mutex as AsyncMutex;
+use twilight_http::error::ErrorType;
+
+use gearbot_2_lib::util::markers::GuildId;
+use gearbot_2_lib::util::GearResult;
+
+use crate::cache::invite::UsedInvite;
+use crate::cache::Invite;
+use crate::BotContext;
+
+impl BotContext {
+    /// Fetch the current invites of a guild, returns none if we are not allowed to see them
+    async fn fetch_invites(&self, guild_id: &GuildId) -> GearResult<Option<HashMap<String, Invite>>> {
+        match self.api_client.guild_invites(*guild_id).exec().await {
+            Ok(response) => Ok(Some(
+                response
+                    .models()
+                    .await?
+                    .into_iter()
+                    .map(|invite| (invite.code.clone(), Invite::from_invite(invite)))
+                    .collect(),
+            )),
+            Err(e) => {
+                // viewing invites requires manage server
+                if matches!(e.kind(), ErrorType::Response { status, .. } if *status == 403) {
+                    return Ok(None);
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Start tracking the invites of a guild so joins can be matched to them
+    pub async fn track_invites(&self, guild_id: &GuildId) -> GearResult<()> {
+        let lock = self.invite_lock(guild_id);
+        let _guard = lock.lock().await;
+
+        if let (Some(guild), Some(invites)) = (self.cache.get_guild(guild_id), self.fetch_invites(guild_id).await?) {
+            guild.set_invites(invites);
+        }
+        Ok(())
+    }
+
+    /// Figure out which invite was used by comparing the use counts with the ones from before the join.
+    /// Returns none if we don't have anything to compare with or if multiple invites could have been used
+    pub async fn find_used_invite(&self, guild_id: &GuildId) -> GearResult<Option<UsedInvite>> {
+        let guild = match self.cache.get_guild(guild_id) {
+            Some(guild) => guild,
+            None => return Ok(None),
+        };
+
+        // joins are handled one at a time so each one compares against the counts after the previous one
+        let lock = self.invite_lock(guild_id);
+        let _guard = lock.lock().await;
+
+        let new = match self.fetch_invites(guild_id).await? {
+            Some(invites) => invites,
+            None => return Ok(None),
+        };
+        let old = match guild.set_invites(new.clone()) {
+            Some(invites) => invites,
+            None => return Ok(None),
+        };
+
+        let mut used = new
+            .values()
+            .filter(|invite| {
+                old.get(&invite.code)
+                    .map_or(invite.uses > 0, |previous| invite.uses > previous.uses)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        // discord deletes limited invites as soon as their last use is taken, so those are gone from the list
+        if used.is_empty() {
+            used = old
+                .into_values()
+                .filter(|invite| {
+                    !new.contains_key(&invite.code) && invite.max_uses > 0 && invite.uses + 1 >= invite.max_uses
+                })
+                .collect();
+        }
+
+        let mut used = used.into_iter();
+        Ok(match (used.next(), used.next()) {
+            (Some(invite), None) => Some(UsedInvite {
+                code: invite.code,
+                inviter: invite.inviter,
+                vanity: false,
+            }),
+            // vanity urls are not part of the invite list, if nothing else got used it must have been that one
+            (None, _) => guild.vanity_invite.clone().map(|code| UsedInvite {
+                code,
+                inviter: None,
+                vanity: true,
+            }),
+            _ => None,
+        })
+    }
+
+    fn invite_lock(&self, guild_id: &GuildId) -> Arc<AsyncMutex<()>> {
+        self.invite_locks.lock().entry(*guild_id).or_default().clone()
+    }
+}
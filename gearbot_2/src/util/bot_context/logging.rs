@@ -0,0 +1,65 @@
+use tracing::error;
+
+use gearbot_2_lib::datastore::guild::{LogCategory, LogDelivery, LogStyle};
+use gearbot_2_lib::util::markers::GuildId;
+use gearbot_2_lib::util::GearResult;
+
+use crate::logging::pump::{LogMessage, WebhookProfile};
+use crate::logging::LogEntry;
+use crate::util::bot_context::BotContext;
+
+impl BotContext {
+    /// Queue an entry for every log channel of a guild that wants this category and whose filters let it through.
+    /// Failures are logged here since the events producing these have nobody to report them to
+    pub async fn log(&self, guild_id: &GuildId, entry: LogEntry) {
+        if let Err(e) = self.queue_log(guild_id, entry).await {
+            error!(
+                "Failed to queue log entry for guild {}: {}",
+                guild_id,
+                e.get_log_error()
+            );
+        }
+    }
+
+    /// Checks if any log channel of the guild wants this category, to skip extra work like audit log lookups
+    pub async fn logs_category(&self, guild_id: &GuildId, category: LogCategory) -> bool {
+        self.get_guild_info(guild_id).await.is_ok_and(|info| {
+            info.config
+                .logging
+                .targets
+                .iter()
+                .any(|target| target.categories.contains(&category))
+        })
+    }
+
+    async fn queue_log(&self, guild_id: &GuildId, entry: LogEntry) -> GearResult<()> {
+        let info = self.get_guild_info(guild_id).await?;
+        let config = &info.config.logging;
+
+        let channels = config.channels_for(
+            &entry.category,
+            entry.channel.as_ref(),
+            entry.user.as_ref(),
+            entry.bot,
+            &entry.roles,
+        );
+
+        let webhook = match config.delivery {
+            LogDelivery::Bot => None,
+            LogDelivery::Webhook => Some(WebhookProfile {
+                name: config.webhook_name.clone(),
+                avatar: config.webhook_avatar.clone(),
+            }),
+        };
+
+        for channel_id in channels {
+            let message = match config.style {
+                LogStyle::Text => LogMessage::Text(entry.to_text()),
+                LogStyle::Embed => LogMessage::Embed(Box::new(entry.to_embed()?)),
+            };
+            self.log_pump.push(channel_id, message, webhook.clone(), &self.metrics);
+        }
+
+        Ok(())
+    }
+}
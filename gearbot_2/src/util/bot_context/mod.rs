@@ -1,11 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::ops::Range;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-use parking_lot::RwLock;
-use tokio::sync::{OnceCell, RwLock as AsyncRwLock, SetError};
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::{Mutex as AsyncMutex, OnceCell, RwLock as AsyncRwLock, SetError};
 use tokio::task::JoinHandle;
 use tracing::info;
 use twilight_gateway::Cluster;
@@ -15,20 +15,36 @@ use uuid::Uuid;
 
 use gearbot_2_lib::datastore::guild::GuildInfo;
 use gearbot_2_lib::datastore::Datastore;
+use gearbot_2_lib::phishing::PhishingDetector;
 use gearbot_2_lib::translations::Translator;
-use gearbot_2_lib::util::markers::{ApplicationId, GuildId};
+use gearbot_2_lib::util::markers::{ApplicationId, GuildId, UserId};
 pub use status::BotStatus;
 
 use crate::cache::Cache;
+use crate::logging::audit::AuditLogCache;
+use crate::logging::pump::LogPump;
+use crate::moderation::anti_spam::SpamTracker;
+use crate::moderation::censor::CensorFilters;
+use crate::moderation::names::NameFilters;
+use crate::moderation::raid::RaidTracker;
+use crate::moderation::verification::VerificationTracker;
 use crate::util::bot_context::cluster_info::ClusterInfo;
+use crate::util::tasks::TaskTracker;
 use crate::Metrics;
 
+mod audit_log;
+mod bans;
 mod cluster_info;
+mod compiled;
 mod guilds;
+mod invites;
+mod logging;
 mod status;
 mod user;
 
 pub type Context = Arc<BotContext>;
+/// Something compiled from the config of a guild, along with the guild info it was compiled from
+type CompiledCache<T> = RwLock<HashMap<GuildId, (Arc<GuildInfo>, Arc<T>)>>;
 
 pub struct BotContext {
     pub translator: Translator,
@@ -38,12 +54,22 @@ pub struct BotContext {
     pub metrics: Metrics,
     pub cache: Cache,
     pub datastore: Datastore,
+    pub log_pump: LogPump,
+    pub audit_logs: AuditLogCache,
+    pub spam_tracker: SpamTracker,
+    pub raid_tracker: RaidTracker,
+    pub verification: VerificationTracker,
+    pub phishing: PhishingDetector,
 
     status: RwLock<BotStatus>,
     pub cluster_info: ClusterInfo,
 
     requested_guilds: HashMap<u64, RwLock<Vec<GuildId>>>,
     pub pending_chunks: HashMap<u64, AtomicBool>,
+    // bans we issued that the ban event hasn't come in for yet
+    issued_bans: Mutex<HashSet<(GuildId, UserId)>>,
+    // used to process invite changes one at a time per guild
+    invite_locks: Mutex<HashMap<GuildId, Arc<AsyncMutex<()>>>>,
 
     //uuid used to identify this instance
     pub uuid: Uuid,
@@ -52,6 +78,12 @@ pub struct BotContext {
 
     /// Config cache
     cached_guild_info: AsyncRwLock<HashMap<GuildId, Arc<GuildInfo>>>,
+    /// Compiled censor filters, along with the guild info they where compiled from
+    censor_filters: CompiledCache<CensorFilters>,
+    /// Compiled name policy filters, these follow the guild info the same way
+    name_filters: CompiledCache<NameFilters>,
+    /// Tasks handling events and commands, shutdown waits for these before flushing the logs
+    pub tasks: Arc<TaskTracker>,
 }
 
 impl BotContext {
@@ -88,6 +120,8 @@ impl BotContext {
             cache: Cache::new_cache(),
             requested_guilds,
             pending_chunks,
+            issued_bans: Default::default(),
+            invite_locks: Default::default(),
             status: RwLock::new(BotStatus::Starting),
             cluster_info: ClusterInfo {
                 cluster_id,
@@ -98,7 +132,16 @@ impl BotContext {
             uuid: Uuid::new_v4(),
             receiver_handle: Default::default(),
             datastore,
+            log_pump: Default::default(),
+            audit_logs: Default::default(),
+            spam_tracker: Default::default(),
+            raid_tracker: Default::default(),
+            verification: Default::default(),
+            phishing: PhishingDetector::from_env(),
             cached_guild_info: Default::default(),
+            censor_filters: Default::default(),
+            name_filters: Default::default(),
+            tasks: Default::default(),
         }
     }
 
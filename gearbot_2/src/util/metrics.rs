@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use actix_web::{HttpRequest, HttpResponse, Responder};
-use prometheus::{Encoder, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+use prometheus::{Encoder, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
 
 use crate::util::bot_context::Context;
 use crate::BotContext;
@@ -19,6 +19,9 @@ pub struct Metrics {
     pub users: IntGauge,
 
     pub status: IntGaugeVec,
+
+    pub logs_dropped: IntCounter,
+    pub logs_failed: IntCounter,
 }
 
 impl Metrics {
@@ -54,6 +57,12 @@ impl Metrics {
         let status = IntGaugeVec::new(Opts::new("status", "Cluster status"), &["status"]).unwrap();
         registry.register(Box::new(status.clone())).unwrap();
 
+        let logs_dropped = IntCounter::new("logs_dropped", "Log entries dropped due to a full log queue").unwrap();
+        registry.register(Box::new(logs_dropped.clone())).unwrap();
+
+        let logs_failed = IntCounter::new("logs_failed", "Log messages that failed to be delivered").unwrap();
+        registry.register(Box::new(logs_failed.clone())).unwrap();
+
         Metrics {
             registry,
             gateway_events,
@@ -62,6 +71,8 @@ impl Metrics {
             members,
             users,
             status,
+            logs_dropped,
+            logs_failed,
         }
     }
 
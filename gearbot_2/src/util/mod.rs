@@ -1,5 +1,6 @@
 pub use metrics::*;
 
 pub mod bot_context;
+pub mod tasks;
 
 mod metrics;
@@ -0,0 +1,50 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+/// Keeps count of the tasks spawned to handle events and commands, so shutdown can wait for them to finish
+/// before flushing the logs they produce
+#[derive(Default)]
+pub struct TaskTracker {
+    running: AtomicUsize,
+    idle: Notify,
+}
+
+impl TaskTracker {
+    pub fn spawn<F>(self: &Arc<Self>, task: F)
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.running.fetch_add(1, Ordering::SeqCst);
+        let guard = TaskGuard(self.clone());
+        tokio::spawn(async move {
+            // dropped even if the task panics, so a broken handler doesn't hold up shutdown
+            let _guard = guard;
+            task.await;
+        });
+    }
+
+    /// Wait until all tracked tasks are done
+    pub async fn wait(&self) {
+        loop {
+            let idle = self.idle.notified();
+            if self.running.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+}
+
+struct TaskGuard(Arc<TaskTracker>);
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if self.0.running.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
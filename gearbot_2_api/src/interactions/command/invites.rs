@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use twilight_http::request::AttachmentFile;
+use twilight_model::application::interaction::ApplicationCommand;
+use twilight_model::guild::Permissions;
+
+use gearbot_2_lib::datastore::guild::GuildDatastore;
+use gearbot_2_lib::translations::GearBotLangKey;
+use gearbot_2_lib::util::error::GearError;
+use gearbot_2_lib::util::GearResult;
+
+use crate::State;
+
+pub async fn async_followup(command: Box<ApplicationCommand>, state: &Arc<State>) -> GearResult<()> {
+    // who invited who is not something everyone needs to see
+    let permissions = command
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .unwrap_or_else(Permissions::empty);
+    if !permissions.contains(Permissions::MANAGE_GUILD) {
+        return Err(GearError::MissingPermissions(Permissions::MANAGE_GUILD));
+    }
+
+    // safe to unwrap as this is not usable in dms
+    let guild_id = command.guild_id.unwrap();
+
+    let info = state.datastore.get_or_create_guild_info(&guild_id).await?;
+    let stats = GuildDatastore::new(&state.datastore, &info.encryption_key, &guild_id)
+        .get_invite_stats()
+        .await?;
+
+    if stats.is_empty() {
+        state
+            .interaction_client()
+            .create_followup_message(&command.token)
+            .content(
+                &state
+                    .translator
+                    .translate(&command.locale, GearBotLangKey::InvitesNone)
+                    .build(),
+            )?
+            .exec()
+            .await?;
+        return Ok(());
+    }
+
+    // one line per invite, can easily be more then fits in a message
+    let lines = stats
+        .iter()
+        .map(|stats| match &stats.inviter {
+            Some(inviter) => format!("{} {} {}", stats.code, stats.joins, inviter),
+            None => format!("{} {}", stats.code, stats.joins),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    state
+        .interaction_client()
+        .create_followup_message(&command.token)
+        .content(
+            &state
+                .translator
+                .translate(&command.locale, GearBotLangKey::InvitesStats)
+                .arg("count", stats.len())
+                .build(),
+        )?
+        .attach(&[AttachmentFile::from_bytes("invites.txt", lines.as_bytes())])
+        .exec()
+        .await?;
+
+    Ok(())
+}
@@ -18,8 +18,11 @@ use gearbot_2_lib::util::GearResult;
 use crate::State;
 
 mod debug;
+mod invites;
 mod ping;
+mod raid;
 mod userinfo;
+mod verification;
 
 pub struct Reply {
     pub response: InteractionResponse,
@@ -32,6 +35,11 @@ pub enum Commands {
     Ping,
     Debug,
     Userinfo,
+    Invites,
+    Raid,
+    RaidEnd,
+    Verification,
+    VerificationPanel,
 }
 
 impl Commands {
@@ -40,16 +48,23 @@ impl Commands {
             "ping" => Some(Self::Ping),
             "debug" => Some(Self::Debug),
             "userinfo" => Some(Self::Userinfo),
+            "invites" => Some(Self::Invites),
+            "raid" => Some(Self::Raid),
+            "verification" => Some(Self::Verification),
             _ => None,
         }
     }
 
     fn has_subcommands(&self) -> bool {
-        false
+        matches!(self, Commands::Raid | Commands::Verification)
     }
 
-    fn parse_into_subcommand(&self, _data: &CommandDataOption) -> Option<Commands> {
-        unreachable!()
+    fn parse_into_subcommand(&self, data: &CommandDataOption) -> Option<Commands> {
+        match (self, data.name.as_str()) {
+            (Commands::Raid, "end") => Some(Commands::RaidEnd),
+            (Commands::Verification, "panel") => Some(Commands::VerificationPanel),
+            _ => None,
+        }
     }
 
     fn execute(
@@ -62,6 +77,12 @@ impl Commands {
             Commands::Ping => defer_async(false),
             Commands::Debug => defer_async(false),
             Commands::Userinfo => defer_async(true),
+            Commands::Invites => defer_async(true),
+            // only the subcommands are ever executed
+            Commands::Raid => unreachable!(),
+            Commands::RaidEnd => defer_async(true),
+            Commands::Verification => unreachable!(),
+            Commands::VerificationPanel => defer_async(true),
         }
     }
 
@@ -70,6 +91,11 @@ impl Commands {
             Commands::Ping => "ping",
             Commands::Debug => "debug",
             Commands::Userinfo => "userinfo",
+            Commands::Invites => "invites",
+            Commands::Raid => "raid",
+            Commands::RaidEnd => "raid_end",
+            Commands::Verification => "verification",
+            Commands::VerificationPanel => "verification_panel",
         }
     }
 
@@ -83,6 +109,11 @@ impl Commands {
             Commands::Ping => ping::async_followup(command, state).await?,
             Commands::Debug => debug::async_followup(command, state).await?,
             Commands::Userinfo => userinfo::async_followup(command, state).await?,
+            Commands::Invites => invites::async_followup(command, state).await?,
+            Commands::Raid => unreachable!(),
+            Commands::RaidEnd => raid::end_followup(command, state).await?,
+            Commands::Verification => unreachable!(),
+            Commands::VerificationPanel => verification::panel_followup(command, state).await?,
         };
         Ok(())
     }
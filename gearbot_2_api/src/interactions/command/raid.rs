@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use twilight_model::application::interaction::ApplicationCommand;
+use twilight_model::guild::Permissions;
+
+use gearbot_2_lib::kafka::message::{InteractionCommand, Message};
+use gearbot_2_lib::util::error::GearError;
+use gearbot_2_lib::util::GearResult;
+
+use crate::State;
+
+pub async fn end_followup(command: Box<ApplicationCommand>, state: &Arc<State>) -> GearResult<()> {
+    // ending a raid undoes punishments so keep it to people who can manage the server
+    let permissions = command
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .unwrap_or_else(Permissions::empty);
+    if !permissions.contains(Permissions::MANAGE_GUILD) {
+        return Err(GearError::MissingPermissions(Permissions::MANAGE_GUILD));
+    }
+
+    // safe to unwrap as this is not usable in dms
+    let guild_id = command.guild_id.unwrap();
+
+    state
+        .kafka_sender
+        .send(
+            &state.queue_for_guild(&guild_id),
+            &Message::new_interaction(
+                command.token,
+                command.locale,
+                InteractionCommand::EndRaid {
+                    guild_id: guild_id.get(),
+                },
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
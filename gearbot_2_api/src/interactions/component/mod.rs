@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use actix_web::HttpResponse;
+use tracing::error;
+use twilight_model::application::callback::{CallbackData, InteractionResponse};
+use twilight_model::application::interaction::MessageComponentInteraction;
+use twilight_model::channel::message::MessageFlags;
+use twilight_util::builder::CallbackDataBuilder;
+
+use gearbot_2_lib::kafka::message::{ComponentInteraction, Message};
+use gearbot_2_lib::util::custom_id::{self, CustomId};
+use gearbot_2_lib::util::markers::{GuildId, UserId};
+use gearbot_2_lib::util::GearResult;
+
+use crate::interactions::command::Reply;
+use crate::State;
+
+mod verification;
+
+/// A reply to a component, the followup means it gets forwarded to the cluster handling the guild
+pub type ComponentResult = GearResult<Reply>;
+
+/// Everything that sends out components, mapped by the prefix of their custom ids
+pub enum Components {
+    Verify,
+}
+
+impl Components {
+    const ALL: [Components; 1] = [Components::Verify];
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            Components::Verify => custom_id::VERIFY,
+        }
+    }
+
+    pub fn parse(custom_id: &CustomId) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|component| component.prefix() == custom_id.prefix)
+    }
+
+    fn execute(&self, interaction: &MessageComponentInteraction, custom_id: &CustomId) -> ComponentResult {
+        match self {
+            Components::Verify => verification::execute(interaction, custom_id),
+        }
+    }
+}
+
+pub async fn handle_component(interaction: Box<MessageComponentInteraction>, state: Arc<State>) -> HttpResponse {
+    let (handler, custom_id) = match CustomId::parse(&interaction.data.custom_id)
+        .and_then(|custom_id| Some((Components::parse(&custom_id)?, custom_id)))
+    {
+        Some(found) => found,
+        None => {
+            error!(
+                "Received a component interaction that can't be mapped to a handler! {}",
+                interaction.data.custom_id
+            );
+            return HttpResponse::BadRequest().body("");
+        }
+    };
+
+    // components are only ever send to guilds, so both of these should be there
+    let (guild_id, user_id) = match (
+        interaction.guild_id,
+        interaction.member.as_ref().and_then(|member| member.user.as_ref()),
+    ) {
+        (Some(guild_id), Some(user)) => (guild_id, user.id),
+        _ => {
+            error!(
+                "Received a component interaction without guild or member! {:?}",
+                interaction
+            );
+            return HttpResponse::BadRequest().body("");
+        }
+    };
+
+    let (response, followup) = match handler.execute(&interaction, &custom_id) {
+        Ok(reply) => (reply.response, reply.followup),
+        Err(error) => {
+            if !error.is_user_error() {
+                error!(
+                    "Failed to handle component interaction: {} (interaction data: {:?})",
+                    error.get_log_error(),
+                    &interaction
+                );
+            }
+            (
+                InteractionResponse::ChannelMessageWithSource(
+                    CallbackDataBuilder::new()
+                        .content(error.get_user_error(&state.translator, &interaction.locale))
+                        .flags(MessageFlags::EPHEMERAL)
+                        .build(),
+                ),
+                false,
+            )
+        }
+    };
+
+    if followup {
+        actix_rt::spawn(async move {
+            if let Err(e) = forward(interaction, guild_id, user_id, &state).await {
+                error!(
+                    "Failed to forward a component interaction to the cluster: {}",
+                    e.get_log_error()
+                );
+            }
+        });
+    }
+
+    let body = serde_json::to_string(&response).expect("InteractionResponse can't be converted to json anymore!");
+    HttpResponse::Ok().body(body)
+}
+
+/// Send a click to the cluster handling the guild, that one answers through the interaction token
+async fn forward(
+    interaction: Box<MessageComponentInteraction>,
+    guild_id: GuildId,
+    user_id: UserId,
+    state: &Arc<State>,
+) -> GearResult<()> {
+    state
+        .kafka_sender
+        .send(
+            &state.queue_for_guild(&guild_id),
+            &Message::new_component(
+                interaction.token,
+                interaction.locale,
+                ComponentInteraction {
+                    custom_id: interaction.data.custom_id,
+                    guild_id: guild_id.get(),
+                    channel_id: interaction.channel_id.get(),
+                    message_id: interaction.message.id.get(),
+                    user_id: user_id.get(),
+                    values: interaction.data.values,
+                },
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Acknowledge now, the cluster sends a new message later on
+fn defer_message(ephemeral: bool) -> ComponentResult {
+    let flags = if ephemeral {
+        MessageFlags::EPHEMERAL
+    } else {
+        MessageFlags::empty()
+    };
+    Ok(Reply {
+        response: InteractionResponse::DeferredChannelMessageWithSource(
+            CallbackDataBuilder::new().flags(flags).build(),
+        ),
+        followup: true,
+    })
+}
+
+/// Acknowledge now, the cluster edits the message the component is on later on
+fn defer_update() -> ComponentResult {
+    Ok(Reply {
+        response: InteractionResponse::DeferredUpdateMessage,
+        followup: true,
+    })
+}
+
+/// Edit the message the component is on right away, and then let the cluster edit it again
+fn update_message(data: CallbackData) -> ComponentResult {
+    Ok(Reply {
+        response: InteractionResponse::UpdateMessage(data),
+        followup: true,
+    })
+}
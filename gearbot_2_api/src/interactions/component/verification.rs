@@ -0,0 +1,44 @@
+use twilight_model::application::component::Component;
+use twilight_model::application::interaction::MessageComponentInteraction;
+use twilight_util::builder::CallbackDataBuilder;
+
+use gearbot_2_lib::util::custom_id::CustomId;
+
+use crate::interactions::component::{defer_message, defer_update, update_message, ComponentResult};
+
+pub fn execute(interaction: &MessageComponentInteraction, custom_id: &CustomId) -> ComponentResult {
+    // the panel itself, the outcome goes in a new message only they can see
+    if custom_id.args.is_empty() {
+        return defer_message(true);
+    }
+
+    // an answer to the challenge, lock the buttons right away so it can only be answered once
+    let components = &interaction.message.components;
+    if components.is_empty() {
+        return defer_update();
+    }
+    update_message(
+        CallbackDataBuilder::new()
+            .content(interaction.message.content.clone())
+            .components(disable_buttons(components))
+            .build(),
+    )
+}
+
+fn disable_buttons(components: &[Component]) -> Vec<Component> {
+    components
+        .iter()
+        .cloned()
+        .map(|component| match component {
+            Component::ActionRow(mut row) => {
+                row.components = disable_buttons(&row.components);
+                Component::ActionRow(row)
+            }
+            Component::Button(mut button) => {
+                button.disabled = true;
+                Component::Button(button)
+            }
+            other => other,
+        })
+        .collect()
+}
@@ -10,6 +10,7 @@ use twilight_model::application::interaction::Interaction;
 use crate::State;
 
 mod command;
+mod component;
 
 #[post("/interactions")]
 pub async fn handle_interactions(body: Bytes, request: HttpRequest) -> impl Responder {
@@ -42,8 +43,10 @@ pub async fn handle_interactions(body: Bytes, request: HttpRequest) -> impl Resp
                             Interaction::ApplicationCommand(command) => {
                                 command::handle_command(command, state.clone()).await
                             }
+                            Interaction::MessageComponent(interaction) => {
+                                component::handle_component(interaction, state.clone()).await
+                            }
                             // Interaction::ApplicationCommandAutocomplete(_) => {}
-                            // Interaction::MessageComponent(_) => {}
                             _ => {
                                 warn!("Unhandled interaction type received! {:?}", interaction);
                                 HttpResponse::InternalServerError().body("")
@@ -39,31 +39,45 @@ pub fn generate_guild_encryption_key(main_encryption_key: &EncryptionKey, guild_
     encrypt_bytes(&guild_encryption_key, main_encryption_key, guild_id)[..32].to_vec()
 }
 
+/// Goes in the upper 4 bytes of the nonce. Snowflakes are unique on their own, but tables that count their
+/// own ids all start at 1 and would reuse nonces under the same guild key without one of these
+#[derive(Clone, Copy)]
+pub enum NonceTag {
+    Snowflake = 0,
+    Infraction = 1,
+    StickyMember = 2,
+}
+
 pub fn encrypt_bytes(plaintext: &[u8], key: &EncryptionKey, msg_id: u64) -> Vec<u8> {
-    let aead = Aes256Gcm::new(&key.0);
+    encrypt_tagged(plaintext, key, msg_id, NonceTag::Snowflake)
+}
 
-    // Since nonce's only never need to be reused, and Discord's snowflakes for messages
-    // are unique, we can use the message id to construct the nonce with its 64 bits, and then
-    // pad the rest with zeros.
-    let mut nonce_bytes = [0u8; 12];
-    let msg_id_bytes = msg_id.to_le_bytes();
-    nonce_bytes[..8].copy_from_slice(&msg_id_bytes);
-    nonce_bytes[8..].copy_from_slice(&[0u8; 4]);
+pub fn decrypt_bytes(ciphertext: &[u8], key: &EncryptionKey, msg_id: u64) -> Vec<u8> {
+    decrypt_tagged(ciphertext, key, msg_id, NonceTag::Snowflake)
+}
 
+pub fn encrypt_tagged(plaintext: &[u8], key: &EncryptionKey, id: u64, tag: NonceTag) -> Vec<u8> {
+    let aead = Aes256Gcm::new(&key.0);
+    let nonce_bytes = build_nonce(id, tag);
     let nonce = GenericArray::from_slice(&nonce_bytes);
 
     aead.encrypt(nonce, plaintext).expect("Failed to encrypt an object!")
 }
 
-pub fn decrypt_bytes(ciphertext: &[u8], key: &EncryptionKey, msg_id: u64) -> Vec<u8> {
+pub fn decrypt_tagged(ciphertext: &[u8], key: &EncryptionKey, id: u64, tag: NonceTag) -> Vec<u8> {
     let aead = Aes256Gcm::new(&key.0);
-
-    let mut nonce_bytes = [0u8; 12];
-    let msg_id_bytes = msg_id.to_le_bytes();
-    nonce_bytes[..8].copy_from_slice(&msg_id_bytes);
-    nonce_bytes[8..].copy_from_slice(&[0u8; 4]);
-
+    let nonce_bytes = build_nonce(id, tag);
     let nonce = GenericArray::from_slice(&nonce_bytes);
 
     aead.decrypt(nonce, ciphertext).expect("Failed to decrypt an object!")
 }
+
+// Since nonce's only never need to be reused, and Discord's snowflakes for messages
+// are unique, we can use the message id to construct the nonce with its 64 bits, and then
+// fill the rest with the tag of what kind of id it is.
+fn build_nonce(id: u64, tag: NonceTag) -> [u8; 12] {
+    let mut nonce_bytes = [0u8; 12];
+    nonce_bytes[..8].copy_from_slice(&id.to_le_bytes());
+    nonce_bytes[8..].copy_from_slice(&(tag as u32).to_le_bytes());
+    nonce_bytes
+}
@@ -1,8 +1,12 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use twilight_model::guild::VerificationLevel;
 
 use crate::datastore::crypto::EncryptionKey;
-use crate::datastore::guild::config::history::{LogStyle, MessageLogs, ModLog, V1Config};
-use crate::datastore::guild::GuildConfigWrapper;
+use crate::datastore::guild::config::history::{
+    LogCategory, LogStyle, MessageLogs, V2Config, DEFAULT_NEW_ACCOUNT_THRESHOLD,
+};
+use crate::datastore::guild::{GuildConfigWrapper, InfractionType};
+use crate::util::markers::{ChannelId, GuildId, RoleId, UserId};
 
 pub struct GuildInfo {
     pub config: GuildConfig,
@@ -11,17 +15,65 @@ pub struct GuildInfo {
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct GuildConfig {
-    pub moderation_logs: ModLog,
+    pub logging: Logging,
     pub message_logs: MessageLogs,
     pub anti_spam: AntiSpam,
+    #[serde(default)]
+    pub raid_protection: RaidProtection,
+    #[serde(default)]
+    pub censoring: Censoring,
+    #[serde(default)]
+    pub phishing: Phishing,
+    #[serde(default)]
+    pub names: NamePolicy,
+    #[serde(default)]
+    pub escalation: Escalation,
+    #[serde(default)]
+    pub sticky_roles: StickyRoles,
+    #[serde(default)]
+    pub auto_roles: AutoRoles,
+    #[serde(default)]
+    pub verification: Verification,
 }
 
-impl From<V1Config> for GuildConfig {
-    fn from(previous: V1Config) -> Self {
+impl From<V2Config> for GuildConfig {
+    fn from(previous: V2Config) -> Self {
+        let mod_log = previous.moderation_logs;
         GuildConfig {
-            moderation_logs: previous.moderation_logs,
+            logging: Logging {
+                style: mod_log.style,
+                new_account_threshold_hours: mod_log.new_account_threshold_hours,
+                thread_members: mod_log.thread_members,
+                delivery: LogDelivery::Bot,
+                webhook_name: None,
+                webhook_avatar: None,
+                // the old single mod log channel becomes a target for everything it used to receive
+                targets: mod_log
+                    .channel
+                    .map(|channel| {
+                        vec![LogTarget {
+                            channel,
+                            categories: mod_log.categories,
+                            filters: LogFilters::default(),
+                        }]
+                    })
+                    .unwrap_or_default(),
+            },
             message_logs: previous.message_logs,
-            anti_spam: AntiSpam { enabled: false },
+            anti_spam: AntiSpam {
+                enabled: previous.anti_spam.enabled,
+                buckets: AntiSpam::default_buckets(),
+                exempt_roles: Vec::new(),
+                exempt_channels: Vec::new(),
+            },
+            raid_protection: RaidProtection::default(),
+            censoring: Censoring::default(),
+            phishing: Phishing::default(),
+            names: NamePolicy::default(),
+            escalation: Escalation::default(),
+            sticky_roles: StickyRoles::default(),
+            auto_roles: AutoRoles::default(),
+            verification: Verification::default(),
         }
     }
 }
@@ -29,20 +81,621 @@ impl From<V1Config> for GuildConfig {
 impl Default for GuildConfig {
     fn default() -> Self {
         GuildConfig {
-            moderation_logs: ModLog { style: LogStyle::Text },
+            logging: Logging {
+                style: LogStyle::Text,
+                new_account_threshold_hours: DEFAULT_NEW_ACCOUNT_THRESHOLD,
+                thread_members: false,
+                delivery: LogDelivery::Bot,
+                webhook_name: None,
+                webhook_avatar: None,
+                targets: Vec::new(),
+            },
             message_logs: MessageLogs { enabled: false },
-            anti_spam: AntiSpam { enabled: false },
+            anti_spam: AntiSpam {
+                enabled: false,
+                buckets: AntiSpam::default_buckets(),
+                exempt_roles: Vec::new(),
+                exempt_channels: Vec::new(),
+            },
+            raid_protection: RaidProtection::default(),
+            censoring: Censoring::default(),
+            phishing: Phishing::default(),
+            names: NamePolicy::default(),
+            escalation: Escalation::default(),
+            sticky_roles: StickyRoles::default(),
+            auto_roles: AutoRoles::default(),
+            verification: Verification::default(),
         }
     }
 }
 
 impl GuildConfig {
     pub fn wrapped(self) -> GuildConfigWrapper {
-        GuildConfigWrapper::V2(self)
+        GuildConfigWrapper::V3(self)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Logging {
+    pub style: LogStyle,
+    pub new_account_threshold_hours: u64,
+    /// Log members being added to or removed from private threads
+    pub thread_members: bool,
+    #[serde(default)]
+    pub delivery: LogDelivery,
+    /// Name and avatar url to post as when delivering through webhooks, the defaults of the webhook are used when unset
+    #[serde(default)]
+    pub webhook_name: Option<String>,
+    #[serde(default)]
+    pub webhook_avatar: Option<String>,
+    pub targets: Vec<LogTarget>,
+}
+
+/// How log messages are posted to the log channels
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LogDelivery {
+    /// Regular messages send by the bot itself
+    #[default]
+    Bot,
+    /// Through a webhook managed by the bot, falling back to regular messages if it can't manage webhooks
+    Webhook,
+}
+
+impl Logging {
+    /// All channels an entry should be send to
+    pub fn channels_for(
+        &self,
+        category: &LogCategory,
+        channel: Option<&ChannelId>,
+        user: Option<&UserId>,
+        bot: bool,
+        roles: &[RoleId],
+    ) -> Vec<ChannelId> {
+        let mut channels = Vec::new();
+        for target in &self.targets {
+            if target.categories.contains(category)
+                && target.filters.allows(channel, user, bot, roles)
+                && !channels.contains(&target.channel)
+            {
+                channels.push(target.channel);
+            }
+        }
+        channels
+    }
+}
+
+/// A channel receiving one or more categories of logs
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LogTarget {
+    pub channel: ChannelId,
+    pub categories: Vec<LogCategory>,
+    #[serde(default)]
+    pub filters: LogFilters,
+}
+
+/// Filters to narrow down what ends up in a log target.
+/// Include lists are only applied when they are not empty, exclusions always win
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFilters {
+    pub include_channels: Vec<ChannelId>,
+    pub exclude_channels: Vec<ChannelId>,
+    pub include_roles: Vec<RoleId>,
+    pub exclude_roles: Vec<RoleId>,
+    pub include_bots: Vec<UserId>,
+    pub exclude_bots: Vec<UserId>,
+    /// Drop everything caused by bots that are not explicitly included
+    pub ignore_bots: bool,
+}
+
+impl LogFilters {
+    /// Checks if an entry about this channel and/or user passes the filters.
+    /// Filters that don't apply to the entry (like channel filters for a member join) are skipped
+    pub fn allows(&self, channel: Option<&ChannelId>, user: Option<&UserId>, bot: bool, roles: &[RoleId]) -> bool {
+        if let Some(channel) = channel {
+            if self.exclude_channels.contains(channel)
+                || (!self.include_channels.is_empty() && !self.include_channels.contains(channel))
+            {
+                return false;
+            }
+        }
+
+        if let Some(user) = user {
+            if roles.iter().any(|role| self.exclude_roles.contains(role))
+                || (!self.include_roles.is_empty() && !roles.iter().any(|role| self.include_roles.contains(role)))
+            {
+                return false;
+            }
+
+            if bot {
+                if self.exclude_bots.contains(user) {
+                    return false;
+                }
+                let included = self.include_bots.contains(user);
+                if !included && (self.ignore_bots || !self.include_bots.is_empty()) {
+                    return false;
+                }
+            }
+        }
+
+        true
     }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AntiSpam {
     pub enabled: bool,
+    #[serde(default = "AntiSpam::default_buckets")]
+    pub buckets: Vec<SpamBucket>,
+    /// Members with any of these roles are never checked
+    #[serde(default)]
+    pub exempt_roles: Vec<RoleId>,
+    #[serde(default)]
+    pub exempt_channels: Vec<ChannelId>,
+}
+
+impl AntiSpam {
+    pub fn default_buckets() -> Vec<SpamBucket> {
+        let bucket = |kind, limit, seconds| SpamBucket {
+            kind,
+            limit,
+            seconds,
+            punishments: vec![Punishment::Delete, Punishment::Timeout { minutes: 10 }],
+        };
+        vec![
+            bucket(SpamType::Messages, 8, 5),
+            bucket(SpamType::DuplicateMessages, 4, 10),
+            bucket(SpamType::Mentions, 10, 15),
+            bucket(SpamType::Links, 10, 30),
+            bucket(SpamType::Attachments, 8, 30),
+            bucket(SpamType::Emoji, 40, 15),
+            bucket(SpamType::Newlines, 60, 15),
+        ]
+    }
+}
+
+/// Triggers when a member goes over the limit within the amount of seconds
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SpamBucket {
+    pub kind: SpamType,
+    pub limit: u32,
+    pub seconds: u32,
+    pub punishments: Vec<Punishment>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum SpamType {
+    Messages,
+    /// Messages with the exact same content
+    DuplicateMessages,
+    Mentions,
+    Links,
+    Attachments,
+    Emoji,
+    Newlines,
+}
+
+/// Actions the bot can take against a member on its own
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Punishment {
+    /// Delete the offending messages
+    Delete,
+    Timeout {
+        /// Longer then discord allows gets cut down to the maximum
+        #[serde(deserialize_with = "clamp_timeout_minutes")]
+        minutes: u32,
+    },
+    Kick,
+    Ban,
+    /// Only recorded in the moderation history, mostly useful to escalate on
+    Warn,
+}
+
+/// Discord refuses timeouts that last longer then 28 days
+const MAX_TIMEOUT_MINUTES: u32 = 28 * 24 * 60;
+
+fn clamp_timeout_minutes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    Ok(u32::deserialize(deserializer)?.min(MAX_TIMEOUT_MINUTES))
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RaidProtection {
+    pub enabled: bool,
+    /// Raid mode starts as soon as any of these windows goes over its limit
+    #[serde(default = "RaidProtection::default_windows")]
+    pub windows: Vec<JoinWindow>,
+    /// Only accounts younger then this are counted as suspicious
+    #[serde(default)]
+    pub max_account_age_hours: Option<u64>,
+    /// Accounts without an avatar are counted as suspicious
+    #[serde(default)]
+    pub default_avatar: bool,
+    /// What to raise the verification level to during a raid, left alone if not set
+    #[serde(default = "RaidProtection::default_verification_level")]
+    pub verification_level: Option<VerificationLevel>,
+    /// Applied to every suspicious account that joins during the raid
+    #[serde(default = "RaidProtection::default_punishments")]
+    pub punishments: Vec<Punishment>,
+}
+
+impl RaidProtection {
+    pub fn default_windows() -> Vec<JoinWindow> {
+        vec![
+            JoinWindow { joins: 10, seconds: 10 },
+            JoinWindow {
+                joins: 30,
+                seconds: 120,
+            },
+        ]
+    }
+
+    pub fn default_verification_level() -> Option<VerificationLevel> {
+        Some(VerificationLevel::High)
+    }
+
+    pub fn default_punishments() -> Vec<Punishment> {
+        vec![Punishment::Timeout { minutes: 24 * 60 }]
+    }
+
+    /// Does a new member look like they could be part of a raid?
+    /// Without any heuristics configured every join counts
+    pub fn is_suspicious(&self, account_age_hours: u64, has_avatar: bool) -> bool {
+        if self.max_account_age_hours.is_none() && !self.default_avatar {
+            return true;
+        }
+        self.max_account_age_hours
+            .is_some_and(|max_age| account_age_hours < max_age)
+            || (self.default_avatar && !has_avatar)
+    }
+}
+
+impl Default for RaidProtection {
+    fn default() -> Self {
+        RaidProtection {
+            enabled: false,
+            windows: RaidProtection::default_windows(),
+            max_account_age_hours: None,
+            default_avatar: false,
+            verification_level: RaidProtection::default_verification_level(),
+            punishments: RaidProtection::default_punishments(),
+        }
+    }
+}
+
+/// Trips when more then this many suspicious accounts join within the amount of seconds
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JoinWindow {
+    pub joins: u32,
+    pub seconds: u32,
+}
+
+/// Content filters for messages, anything matching gets removed
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Censoring {
+    pub enabled: bool,
+    /// Matched as whole words, ignoring case
+    #[serde(default)]
+    pub words: Vec<String>,
+    #[serde(default)]
+    pub regexes: Vec<String>,
+    /// Remove invites to other servers
+    #[serde(default)]
+    pub invites: bool,
+    /// Servers invites are still allowed to, the guild itself is always allowed
+    #[serde(default)]
+    pub invite_allowlist: Vec<GuildId>,
+    /// Links to these domains (or their subdomains) are removed
+    #[serde(default)]
+    pub blocked_domains: Vec<String>,
+    /// If not empty, links to any domain not in here are removed
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    #[serde(default)]
+    pub exempt_roles: Vec<RoleId>,
+    #[serde(default)]
+    pub exempt_channels: Vec<ChannelId>,
+}
+
+/// Scanning links against known scam domains and discord lookalikes
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Phishing {
+    pub enabled: bool,
+    #[serde(default = "Phishing::default_punishments")]
+    pub punishments: Vec<Punishment>,
+    #[serde(default)]
+    pub exempt_roles: Vec<RoleId>,
+}
+
+impl Phishing {
+    pub fn default_punishments() -> Vec<Punishment> {
+        vec![Punishment::Delete, Punishment::Timeout { minutes: 60 }]
+    }
+}
+
+impl Default for Phishing {
+    fn default() -> Self {
+        Phishing {
+            enabled: false,
+            punishments: Phishing::default_punishments(),
+            exempt_roles: Vec::new(),
+        }
+    }
+}
+
+/// Rules for the names members show up with, checked when they join and whenever their nickname or username changes
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NamePolicy {
+    pub enabled: bool,
+    /// Strip characters from the start of names that are only there to end up at the top of the member list
+    #[serde(default = "NamePolicy::default_dehoist")]
+    pub dehoist: bool,
+    #[serde(default = "NamePolicy::default_hoist_characters")]
+    pub hoist_characters: String,
+    /// Clean up names that are piled full of combining characters
+    #[serde(default)]
+    pub zalgo: bool,
+    /// Matched anywhere in the name, after folding lookalike characters
+    #[serde(default)]
+    pub words: Vec<String>,
+    #[serde(default)]
+    pub regexes: Vec<String>,
+    /// Nickname to give members whose name breaks the rules, without one they are only logged
+    #[serde(default)]
+    pub replacement: Option<String>,
+    #[serde(default)]
+    pub exempt_roles: Vec<RoleId>,
+}
+
+impl NamePolicy {
+    pub fn default_dehoist() -> bool {
+        true
+    }
+
+    pub fn default_hoist_characters() -> String {
+        "!\"#$%&'()*+,-./:;<=>?@[]^_`{|}~".to_string()
+    }
+}
+
+impl Default for NamePolicy {
+    fn default() -> Self {
+        NamePolicy {
+            enabled: false,
+            dehoist: true,
+            hoist_characters: NamePolicy::default_hoist_characters(),
+            zalgo: false,
+            words: Vec::new(),
+            regexes: Vec::new(),
+            replacement: None,
+            exempt_roles: Vec::new(),
+        }
+    }
+}
+
+/// Harsher punishments for repeat offenders
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Escalation {
+    pub enabled: bool,
+    /// Ordered from mild to severe, only the most severe step that triggers is applied
+    #[serde(default)]
+    pub steps: Vec<EscalationStep>,
+}
+
+/// Triggers when a member collects this many infractions of a type within the amount of days
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EscalationStep {
+    pub infraction: InfractionType,
+    pub count: u32,
+    pub days: u32,
+    pub punishment: Punishment,
+}
+
+/// Give members their roles and nickname back when they leave and rejoin, so leaving doesn't shake off a mute role
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StickyRoles {
+    pub enabled: bool,
+    /// How long after leaving members still get everything back
+    #[serde(default = "StickyRoles::default_hours")]
+    pub hours: u32,
+    /// Only restore these roles, all roles are restored when this is empty
+    #[serde(default)]
+    pub include_roles: Vec<RoleId>,
+    /// Never restore these, even if they are included
+    #[serde(default)]
+    pub exclude_roles: Vec<RoleId>,
+    #[serde(default = "StickyRoles::default_nickname")]
+    pub nickname: bool,
+}
+
+impl StickyRoles {
+    pub fn default_hours() -> u32 {
+        30 * 24
+    }
+
+    pub fn default_nickname() -> bool {
+        true
+    }
+
+    pub fn restores(&self, role: &RoleId) -> bool {
+        !self.exclude_roles.contains(role) && (self.include_roles.is_empty() || self.include_roles.contains(role))
+    }
+}
+
+impl Default for StickyRoles {
+    fn default() -> Self {
+        StickyRoles {
+            enabled: false,
+            hours: StickyRoles::default_hours(),
+            include_roles: Vec::new(),
+            exclude_roles: Vec::new(),
+            nickname: true,
+        }
+    }
+}
+
+/// Roles handed out to everyone that joins
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct AutoRoles {
+    pub enabled: bool,
+    /// Hold off until members passed membership screening, guilds without screening get them on join
+    #[serde(default)]
+    pub after_screening: bool,
+    #[serde(default)]
+    pub roles: Vec<AutoRole>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AutoRole {
+    pub role: RoleId,
+    /// Seconds to wait before handing out the role
+    #[serde(default)]
+    pub delay: u64,
+    #[serde(default)]
+    pub exclude_bots: bool,
+}
+
+/// A panel with a button new members have to click before they get access to the rest of the server
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Verification {
+    pub enabled: bool,
+    /// Handed out once verified, nothing can be verified without one
+    #[serde(default)]
+    pub role: Option<RoleId>,
+    /// Accounts younger than this can't verify
+    #[serde(default)]
+    pub min_account_age_hours: Option<u64>,
+    /// Make members pick the right picture out of a few before verifying them
+    #[serde(default)]
+    pub captcha: bool,
+    #[serde(default = "Verification::default_message")]
+    pub message: String,
+    #[serde(default = "Verification::default_button_label")]
+    pub button_label: String,
+}
+
+impl Verification {
+    pub fn default_message() -> String {
+        "Welcome! Click the button below to verify yourself and get access to the rest of the server.".to_string()
+    }
+
+    pub fn default_button_label() -> String {
+        "Verify".to_string()
+    }
+}
+
+impl Default for Verification {
+    fn default() -> Self {
+        Verification {
+            enabled: false,
+            role: None,
+            min_account_age_hours: None,
+            captcha: false,
+            message: Verification::default_message(),
+            button_label: Verification::default_button_label(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_join_is_suspicious_without_heuristics() {
+        let config = RaidProtection::default();
+        assert!(config.is_suspicious(10_000, true));
+        assert!(config.is_suspicious(0, false));
+    }
+
+    #[test]
+    fn flags_young_accounts() {
+        let config = RaidProtection {
+            max_account_age_hours: Some(24),
+            ..RaidProtection::default()
+        };
+        assert!(config.is_suspicious(23, true));
+        assert!(!config.is_suspicious(24, true));
+        // avatars don't matter unless asked for
+        assert!(!config.is_suspicious(100, false));
+    }
+
+    #[test]
+    fn flags_default_avatars() {
+        let config = RaidProtection {
+            default_avatar: true,
+            ..RaidProtection::default()
+        };
+        assert!(config.is_suspicious(10_000, false));
+        assert!(!config.is_suspicious(0, true));
+
+        let both = RaidProtection {
+            max_account_age_hours: Some(24),
+            ..config
+        };
+        assert!(both.is_suspicious(0, true));
+        assert!(both.is_suspicious(100, false));
+        assert!(!both.is_suspicious(100, true));
+    }
+
+    fn filters() -> LogFilters {
+        LogFilters {
+            include_channels: vec![ChannelId::new(1), ChannelId::new(2)],
+            exclude_channels: vec![ChannelId::new(2)],
+            exclude_roles: vec![RoleId::new(10)],
+            ..LogFilters::default()
+        }
+    }
+
+    #[test]
+    fn allows_everything_by_default() {
+        let filters = LogFilters::default();
+        assert!(filters.allows(Some(&ChannelId::new(1)), Some(&UserId::new(1)), true, &[RoleId::new(1)]));
+        assert!(filters.allows(None, None, false, &[]));
+    }
+
+    #[test]
+    fn filters_channels() {
+        let filters = filters();
+        assert!(filters.allows(Some(&ChannelId::new(1)), None, false, &[]));
+        // exclusions win over inclusions
+        assert!(!filters.allows(Some(&ChannelId::new(2)), None, false, &[]));
+        assert!(!filters.allows(Some(&ChannelId::new(3)), None, false, &[]));
+        // entries without a channel skip the channel filters
+        assert!(filters.allows(None, Some(&UserId::new(1)), false, &[]));
+    }
+
+    #[test]
+    fn filters_roles() {
+        let mut filters = filters();
+        let user = UserId::new(1);
+        assert!(filters.allows(None, Some(&user), false, &[RoleId::new(11)]));
+        assert!(!filters.allows(None, Some(&user), false, &[RoleId::new(11), RoleId::new(10)]));
+
+        filters.include_roles = vec![RoleId::new(12)];
+        assert!(!filters.allows(None, Some(&user), false, &[RoleId::new(11)]));
+        assert!(filters.allows(None, Some(&user), false, &[RoleId::new(12)]));
+        // role filters only apply to entries about a user
+        assert!(filters.allows(None, None, false, &[]));
+    }
+
+    #[test]
+    fn filters_bots() {
+        let bot = UserId::new(1);
+        let other_bot = UserId::new(2);
+
+        let filters = LogFilters {
+            ignore_bots: true,
+            include_bots: vec![bot],
+            ..LogFilters::default()
+        };
+        assert!(filters.allows(None, Some(&bot), true, &[]));
+        assert!(!filters.allows(None, Some(&other_bot), true, &[]));
+        assert!(filters.allows(None, Some(&other_bot), false, &[]));
+
+        let filters = LogFilters {
+            exclude_bots: vec![bot],
+            ..LogFilters::default()
+        };
+        assert!(!filters.allows(None, Some(&bot), true, &[]));
+        assert!(filters.allows(None, Some(&other_bot), true, &[]));
+    }
 }
@@ -1,14 +1,55 @@
 use serde::{Deserialize, Serialize};
 
+use crate::util::markers::ChannelId;
+
+/// How many hours old an account can be before it's no longer flagged as new in the join logs
+pub const DEFAULT_NEW_ACCOUNT_THRESHOLD: u64 = 7 * 24;
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct V1Config {
     pub moderation_logs: ModLog,
     pub message_logs: MessageLogs,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct V2Config {
+    pub moderation_logs: ModLog,
+    pub message_logs: MessageLogs,
+    pub anti_spam: AntiSpam,
+}
+
+impl From<V1Config> for V2Config {
+    fn from(previous: V1Config) -> Self {
+        V2Config {
+            moderation_logs: previous.moderation_logs,
+            message_logs: previous.message_logs,
+            anti_spam: AntiSpam { enabled: false },
+        }
+    }
+}
+
+/// Anti-spam as it was in V2, before it had anything to configure
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AntiSpam {
+    pub enabled: bool,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ModLog {
     pub style: LogStyle,
+    #[serde(default)]
+    pub channel: Option<ChannelId>,
+    #[serde(default = "LogCategory::all")]
+    pub categories: Vec<LogCategory>,
+    #[serde(default = "default_new_account_threshold")]
+    pub new_account_threshold_hours: u64,
+    /// Log members being added to or removed from private threads
+    #[serde(default)]
+    pub thread_members: bool,
+}
+
+fn default_new_account_threshold() -> u64 {
+    DEFAULT_NEW_ACCOUNT_THRESHOLD
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -21,3 +62,28 @@ pub enum LogStyle {
     Text,
     Embed,
 }
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LogCategory {
+    Messages,
+    Members,
+    Roles,
+    Channels,
+    Voice,
+    Moderation,
+    Server,
+}
+
+impl LogCategory {
+    pub fn all() -> Vec<LogCategory> {
+        vec![
+            LogCategory::Messages,
+            LogCategory::Members,
+            LogCategory::Roles,
+            LogCategory::Channels,
+            LogCategory::Voice,
+            LogCategory::Moderation,
+            LogCategory::Server,
+        ]
+    }
+}
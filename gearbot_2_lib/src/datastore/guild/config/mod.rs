@@ -4,15 +4,21 @@ use sqlx::FromRow;
 
 pub use guild_config::GuildConfig;
 pub use guild_config::GuildInfo;
+pub use guild_config::{
+    AntiSpam, AutoRole, AutoRoles, Censoring, Escalation, EscalationStep, JoinWindow, LogDelivery, LogFilters,
+    LogTarget, Logging, NamePolicy, Phishing, Punishment, RaidProtection, SpamBucket, SpamType, StickyRoles,
+    Verification,
+};
+pub use history::{LogCategory, LogStyle};
 
 use crate::datastore::crypto::EncryptionKey;
-use crate::datastore::guild::config::history::V1Config;
+use crate::datastore::guild::config::history::{V1Config, V2Config};
 
 mod guild_config;
 mod history;
 
 /// The highest config version this application knows about and supports
-pub const CURRENT_CONFIG_VERSION: i32 = 2;
+pub const CURRENT_CONFIG_VERSION: i32 = 3;
 
 #[derive(FromRow)]
 pub struct DatabaseGuildInfo {
@@ -40,9 +46,11 @@ impl DatabaseGuildInfo {
 /// names must start be V followed by a number, postgres uses this to extract and store the version number
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "version")]
+#[allow(clippy::large_enum_variant)]
 pub enum GuildConfigWrapper {
     V1(V1Config),
-    V2(GuildConfig),
+    V2(V2Config),
+    V3(GuildConfig),
 }
 
 impl GuildConfigWrapper {
@@ -50,7 +58,7 @@ impl GuildConfigWrapper {
         let mut current = self;
         loop {
             match current {
-                GuildConfigWrapper::V2(config) => {
+                GuildConfigWrapper::V3(config) => {
                     return config;
                 }
                 outdated => current = outdated.migrate(),
@@ -61,7 +69,83 @@ impl GuildConfigWrapper {
     fn migrate(self) -> Self {
         match self {
             GuildConfigWrapper::V1(inner) => GuildConfigWrapper::V2(inner.into()),
+            GuildConfigWrapper::V2(inner) => GuildConfigWrapper::V3(inner.into()),
             _ => panic!("Tried to migrate a fully migrated config!"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::util::markers::ChannelId;
+
+    use super::*;
+
+    fn migrate(fixture: &str) -> GuildConfig {
+        serde_json::from_str::<GuildConfigWrapper>(fixture)
+            .unwrap()
+            .into_config()
+    }
+
+    #[test]
+    fn migrates_v1() {
+        let config = migrate(include_str!("fixtures/v1.json"));
+        assert!(matches!(config.logging.style, LogStyle::Embed));
+        assert_eq!(
+            config.logging.new_account_threshold_hours,
+            history::DEFAULT_NEW_ACCOUNT_THRESHOLD
+        );
+        assert!(!config.logging.thread_members);
+        assert_eq!(config.logging.delivery, LogDelivery::Bot);
+        // there was no log channel yet, so nothing to route to
+        assert!(config.logging.targets.is_empty());
+        assert!(config.message_logs.enabled);
+        assert!(!config.anti_spam.enabled);
+    }
+
+    #[test]
+    fn migrates_v2_from_before_log_channels() {
+        let config = migrate(include_str!("fixtures/v2_before_log_channels.json"));
+        assert!(matches!(config.logging.style, LogStyle::Text));
+        assert_eq!(
+            config.logging.new_account_threshold_hours,
+            history::DEFAULT_NEW_ACCOUNT_THRESHOLD
+        );
+        assert!(config.logging.targets.is_empty());
+        assert!(config.anti_spam.enabled);
+        assert_eq!(config.anti_spam.buckets.len(), AntiSpam::default_buckets().len());
+    }
+
+    #[test]
+    fn migrates_v2_log_channel_into_a_target() {
+        let config = migrate(include_str!("fixtures/v2.json"));
+        assert!(matches!(config.logging.style, LogStyle::Embed));
+        assert_eq!(config.logging.new_account_threshold_hours, 48);
+        assert!(config.logging.thread_members);
+        assert_eq!(config.logging.targets.len(), 1);
+
+        let channel = ChannelId::new(123456789012345678);
+        let target = &config.logging.targets[0];
+        assert_eq!(target.channel, channel);
+        assert_eq!(target.categories, vec![LogCategory::Members, LogCategory::Moderation]);
+        assert_eq!(
+            config
+                .logging
+                .channels_for(&LogCategory::Members, None, None, false, &[]),
+            vec![channel]
+        );
+        assert!(config
+            .logging
+            .channels_for(&LogCategory::Messages, None, None, false, &[])
+            .is_empty());
+    }
+
+    #[test]
+    fn keeps_current_configs() {
+        let config = GuildConfig::default();
+        let json = serde_json::to_string(&config.wrapped()).unwrap();
+        assert!(json.contains(r#""version":"V3""#));
+        let config = migrate(&json);
+        assert!(config.logging.targets.is_empty());
+    }
+}
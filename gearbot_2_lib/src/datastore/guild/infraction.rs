@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use sqlx::query;
+
+use crate::datastore::crypto::{encrypt_tagged, NonceTag};
+use crate::datastore::guild::GuildDatastore;
+use crate::datastore::DatastoreResult;
+use crate::util::markers::UserId;
+
+/// The kinds of moderation actions we keep history of.
+/// These are stored as numbers so never re-order or remove any, only add new ones at the end
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum InfractionType {
+    Ban = 0,
+    Unban = 1,
+    Kick = 2,
+    Timeout = 3,
+    Warn = 4,
+}
+
+impl GuildDatastore<'_> {
+    /// store a moderation action, regardless of who performed it.
+    /// The moderator is unknown for actions done outside the bot that we could not attribute
+    pub async fn store_infraction(
+        &self,
+        target: &UserId,
+        moderator: Option<&UserId>,
+        kind: InfractionType,
+        reason: Option<&str>,
+        active: bool,
+    ) -> DatastoreResult<i64> {
+        // reserve the id first, it's used as nonce for encrypting the reason
+        let id = query!(r#"SELECT nextval('infraction_id_seq') as "id!""#)
+            .fetch_one(&self.pool)
+            .await?
+            .id;
+
+        let encrypted_reason = reason
+            .map(|reason| encrypt_tagged(reason.as_bytes(), self.encryption_key, id as u64, NonceTag::Infraction));
+        query!(
+            r#"
+        INSERT INTO infraction
+        (id, guild, target, moderator, type, reason, active)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, $7)"#,
+            id,
+            &self.guild_id,
+            target.get() as i64,
+            moderator.map(|moderator| moderator.get() as i64),
+            kind as i32,
+            encrypted_reason,
+            active
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// mark all active infractions of a type as no longer active, for example old bans after an unban
+    pub async fn deactivate_infractions(&self, target: &UserId, kind: InfractionType) -> DatastoreResult<()> {
+        query!(
+            "UPDATE infraction SET active=false WHERE guild=$1 AND target=$2 AND type=$3 AND active",
+            &self.guild_id,
+            target.get() as i64,
+            kind as i32
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// count how many infractions of a type a user got within the last amount of days
+    pub async fn count_recent_infractions(
+        &self,
+        target: &UserId,
+        kind: InfractionType,
+        days: u32,
+    ) -> DatastoreResult<i64> {
+        let count = query!(
+            r#"SELECT count(*) as "count!" FROM infraction WHERE guild=$1 AND target=$2 AND type=$3 AND created_at > now() - make_interval(days => $4)"#,
+            &self.guild_id,
+            target.get() as i64,
+            kind as i32,
+            days as i32
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+
+        Ok(count)
+    }
+}
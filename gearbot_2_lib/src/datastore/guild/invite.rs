@@ -0,0 +1,56 @@
+use sqlx::{query, query_as, FromRow};
+
+use crate::datastore::guild::GuildDatastore;
+use crate::datastore::DatastoreResult;
+use crate::util::markers::UserId;
+
+#[derive(FromRow)]
+struct RawInviteStats {
+    code: String,
+    inviter: Option<i64>,
+    joins: i64,
+}
+
+pub struct InviteStats {
+    pub code: String,
+    pub inviter: Option<UserId>,
+    pub joins: u64,
+}
+
+impl GuildDatastore<'_> {
+    /// record that a member joined through an invite
+    pub async fn store_invite_use(&self, code: &str, inviter: Option<&UserId>, member: &UserId) -> DatastoreResult<()> {
+        query!(
+            "INSERT INTO invite_use (guild, code, inviter, member) VALUES ($1, $2, $3, $4)",
+            &self.guild_id,
+            code,
+            inviter.map(|inviter| inviter.get() as i64),
+            member.get() as i64
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// how many members joined through each invite, most used first
+    pub async fn get_invite_stats(&self) -> DatastoreResult<Vec<InviteStats>> {
+        let raw = query_as!(
+            RawInviteStats,
+            r#"SELECT code, max(inviter) as inviter, count(*) as "joins!" FROM invite_use WHERE guild=$1 GROUP BY code ORDER BY count(*) DESC"#,
+            &self.guild_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(raw
+            .into_iter()
+            .map(|raw| InviteStats {
+                code: raw.code,
+                // safe to unwrap, these where stored from real user ids so can't be 0
+                inviter: raw.inviter.map(|inviter| UserId::new(inviter as u64)),
+                joins: raw.joins as u64,
+            })
+            .collect())
+    }
+}
@@ -1,17 +1,44 @@
 use std::ops::Deref;
 
+pub use config::AntiSpam;
+pub use config::AutoRole;
+pub use config::AutoRoles;
+pub use config::Censoring;
 pub use config::DatabaseGuildInfo;
+pub use config::Escalation;
+pub use config::EscalationStep;
 pub use config::GuildConfig;
 pub use config::GuildConfigWrapper;
 pub use config::GuildInfo;
+pub use config::JoinWindow;
+pub use config::LogCategory;
+pub use config::LogDelivery;
+pub use config::LogFilters;
+pub use config::LogStyle;
+pub use config::LogTarget;
+pub use config::Logging;
+pub use config::NamePolicy;
+pub use config::Phishing;
+pub use config::Punishment;
+pub use config::RaidProtection;
+pub use config::SpamBucket;
+pub use config::SpamType;
+pub use config::StickyRoles;
+pub use config::Verification;
 pub use config::CURRENT_CONFIG_VERSION;
+pub use infraction::InfractionType;
+pub use invite::InviteStats;
+pub use sticky_member::StickyMember;
 
 use crate::datastore::crypto::EncryptionKey;
 use crate::datastore::Datastore;
 use crate::util::markers::GuildId;
 
 mod config;
+mod infraction;
+mod invite;
 mod message;
+mod sticky_member;
 
 pub struct GuildDatastore<'a> {
     master_datastore: &'a Datastore,
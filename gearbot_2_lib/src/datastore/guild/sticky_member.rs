@@ -0,0 +1,83 @@
+use sqlx::query;
+
+use crate::datastore::crypto::{decrypt_tagged, encrypt_tagged, NonceTag};
+use crate::datastore::guild::GuildDatastore;
+use crate::datastore::DatastoreResult;
+use crate::util::markers::{RoleId, UserId};
+
+/// What a member had when they left, to give back when they return
+pub struct StickyMember {
+    pub roles: Vec<RoleId>,
+    pub nickname: Option<String>,
+}
+
+impl GuildDatastore<'_> {
+    /// remember the roles and nickname of a member that left, replacing anything from an earlier leave
+    pub async fn store_sticky_member(
+        &self,
+        member: &UserId,
+        roles: &[RoleId],
+        nickname: Option<&str>,
+    ) -> DatastoreResult<()> {
+        // every store gets a fresh id, it's used as nonce for encrypting the nickname
+        let id = query!(r#"SELECT nextval('sticky_member_id_seq') as "id!""#)
+            .fetch_one(&self.pool)
+            .await?
+            .id;
+
+        let roles = roles.iter().map(|role| role.get() as i64).collect::<Vec<_>>();
+        let encrypted_nickname = nickname.map(|nickname| {
+            encrypt_tagged(
+                nickname.as_bytes(),
+                self.encryption_key,
+                id as u64,
+                NonceTag::StickyMember,
+            )
+        });
+        query!(
+            r#"
+        INSERT INTO sticky_member
+        (id, guild, member, roles, nickname)
+        VALUES
+        ($1, $2, $3, $4, $5)
+        ON CONFLICT (guild, member) DO UPDATE
+        SET id=excluded.id, roles=excluded.roles, nickname=excluded.nickname, left_at=excluded.left_at"#,
+            id,
+            &self.guild_id,
+            member.get() as i64,
+            &roles,
+            encrypted_nickname
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// take out what we remembered about a returning member, if they left less than the amount of hours ago.
+    /// Anything stored is removed either way, it's only given back once
+    pub async fn take_sticky_member(&self, member: &UserId, hours: u32) -> DatastoreResult<Option<StickyMember>> {
+        let raw = query!(
+            r#"DELETE FROM sticky_member WHERE guild=$1 AND member=$2 RETURNING id, roles, nickname, left_at > now() - make_interval(hours => $3) as "recent!""#,
+            &self.guild_id,
+            member.get() as i64,
+            hours as i32
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(raw.filter(|raw| raw.recent).map(|raw| StickyMember {
+            // safe, these where stored from real role ids so can't be 0
+            roles: raw.roles.into_iter().map(|role| RoleId::new(role as u64)).collect(),
+            nickname: raw.nickname.map(|nickname| {
+                String::from_utf8_lossy(&decrypt_tagged(
+                    &nickname,
+                    self.encryption_key,
+                    raw.id as u64,
+                    NonceTag::StickyMember,
+                ))
+                .to_string()
+            }),
+        }))
+    }
+}
@@ -8,12 +8,25 @@ pub enum Message {
         locale: String,
         command: InteractionCommand,
     },
+    Component {
+        token: String,
+        locale: String,
+        component: ComponentInteraction,
+    },
 }
 
 impl Message {
     pub fn new_interaction(token: String, locale: String, command: InteractionCommand) -> Self {
         Message::Interaction { token, locale, command }
     }
+
+    pub fn new_component(token: String, locale: String, component: ComponentInteraction) -> Self {
+        Message::Component {
+            token,
+            locale,
+            component,
+        }
+    }
 }
 
 #[derive(Encode, Decode, Debug)]
@@ -26,4 +39,19 @@ pub enum General {
 pub enum InteractionCommand {
     Debug { component: String, guild_id: u64 },
     Userinfo { user_id: u64, guild_id: u64 },
+    EndRaid { guild_id: u64 },
+    VerificationPanel { guild_id: u64, channel_id: u64 },
+}
+
+/// A click on one of our message components, routed to the cluster handling the guild
+#[derive(Encode, Decode, Debug)]
+pub struct ComponentInteraction {
+    /// Still encoded, the cluster parses it again to find the handler
+    pub custom_id: String,
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub message_id: u64,
+    pub user_id: u64,
+    /// The picked options for select menus
+    pub values: Vec<String>,
 }
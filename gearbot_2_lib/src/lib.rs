@@ -1,4 +1,5 @@
 pub mod datastore;
 pub mod kafka;
+pub mod phishing;
 pub mod translations;
 pub mod util;
@@ -0,0 +1,114 @@
+use crate::util::confusables::fold_confusables;
+
+/// Domains scammers like to imitate
+const TARGETS: [&str; 2] = ["discord.com", "discord.gift"];
+/// Names need to be at least this long before a typo counts as imitating them, anything shorter has too many real neighbours
+const MIN_TYPO_LENGTH: usize = 7;
+/// Words that show up in nearly every fake nitro domain
+const BAIT: [&str; 5] = ["nitro", "gift", "free", "steam", "airdrop"];
+
+/// Decode punycode labels and lowercase, so lists and links are compared the same way
+pub fn normalize(domain: &str) -> String {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    let (unicode, result) = idna::domain_to_unicode(&domain);
+    match result {
+        Ok(()) => unicode,
+        // not valid punycode, keep what we got
+        Err(_) => domain,
+    }
+}
+
+/// Check if a (normalized, non official) domain is pretending to be one of discord's domains
+pub fn impersonates(domain: &str) -> Option<&'static str> {
+    let skeleton = fold_confusables(domain);
+    let labels = skeleton.split('.').collect::<Vec<_>>();
+    if labels.len() < 2 {
+        return None;
+    }
+    let name = labels[labels.len() - 2];
+    let tld = labels[labels.len() - 1];
+
+    for target in TARGETS {
+        let (target_name, target_tld) = target.split_once('.').unwrap();
+        if tld != target_tld {
+            continue;
+        }
+        // the real thing written with lookalike characters, or a single typo like dlscord.gift.
+        // Names that only add to it (discords.com) are left alone, plenty of legit sites do that,
+        // and so are bigger differences since those catch real sites like discogs.com
+        if name == target_name
+            || (target_name.len() >= MIN_TYPO_LENGTH
+                && distance(name, target_name) <= 1
+                && !name.starts_with(target_name))
+        {
+            return Some(target);
+        }
+    }
+
+    // discord-nitro.com, steamdiscordgift.ru and friends
+    if contains_similar(&name.replace('-', ""), "discord") && BAIT.iter().any(|bait| skeleton.contains(bait)) {
+        return Some("discord.gift");
+    }
+
+    None
+}
+
+/// Does the text contain something within one edit of the needle
+fn contains_similar(text: &str, needle: &str) -> bool {
+    let text = text.chars().collect::<Vec<_>>();
+    let length = needle.chars().count();
+    (length - 1..=length + 1).any(|size| {
+        size <= text.len()
+            && text
+                .windows(size)
+                .any(|window| distance(&window.iter().collect::<String>(), needle) <= 1)
+    })
+}
+
+/// Levenshtein distance between two strings
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + (a_char != *b_char) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_lookalikes() {
+        assert_eq!(impersonates("dіscord.com"), Some("discord.com"));
+        assert_eq!(impersonates("disc0rd.com"), Some("discord.com"));
+        assert_eq!(impersonates("dlscord.com"), Some("discord.com"));
+        assert_eq!(impersonates("discrd.gift"), Some("discord.gift"));
+        assert_eq!(impersonates("discord-nitro.com"), Some("discord.gift"));
+        assert_eq!(impersonates("steamdiscordgift.ru"), Some("discord.gift"));
+    }
+
+    #[test]
+    fn ignores_near_misses() {
+        assert_eq!(impersonates("discogs.com"), None);
+        assert_eq!(impersonates("discords.com"), None);
+        assert_eq!(impersonates("discover.com"), None);
+        assert_eq!(impersonates("disco.com"), None);
+        assert_eq!(impersonates("discord.net"), None);
+        assert_eq!(impersonates("example.com"), None);
+    }
+
+    #[test]
+    fn normalizes_domains() {
+        assert_eq!(normalize(" Example.COM. "), "example.com");
+        assert_eq!(normalize("xn--dscord-pvf.com"), "dіscord.com");
+        // broken punycode is kept as is
+        assert_eq!(normalize("xn--zz.com"), "xn--zz.com");
+    }
+}
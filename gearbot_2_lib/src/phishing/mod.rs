@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::{env, fs, io};
+
+use parking_lot::RwLock;
+
+pub use links::extract_domains;
+
+mod links;
+mod lookalike;
+
+/// Domains that belong to discord itself, these (and their subdomains) are never flagged
+const OFFICIAL_DOMAINS: [&str; 9] = [
+    "discord.com",
+    "discord.gg",
+    "discord.gift",
+    "discord.gifts",
+    "discord.media",
+    "discord.new",
+    "discordapp.com",
+    "discordapp.net",
+    "discordstatus.com",
+];
+
+/// Why a domain was flagged
+pub enum PhishingMatch {
+    /// The domain (or one of its parents) is on the domain list
+    Listed(String),
+    /// The domain looks a lot like one of discord's own
+    Lookalike { domain: String, target: &'static str },
+}
+
+impl Display for PhishingMatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PhishingMatch::Listed(domain) => write!(f, "`{}` is a known scam domain", domain),
+            PhishingMatch::Lookalike { domain, target } => {
+                write!(f, "`{}` is pretending to be `{}`", domain, target)
+            }
+        }
+    }
+}
+
+/// Checks links against a locally stored list of known scam domains and a couple of heuristics
+/// for domains that are pretending to be discord.
+/// The list is a plain text file with one domain per line, set through the `PHISHING_DOMAINS_FILE` env variable
+pub struct PhishingDetector {
+    path: Option<PathBuf>,
+    domains: RwLock<HashSet<String>>,
+}
+
+impl PhishingDetector {
+    pub fn from_env() -> Self {
+        PhishingDetector {
+            path: env::var("PHISHING_DOMAINS_FILE").ok().map(PathBuf::from),
+            domains: Default::default(),
+        }
+    }
+
+    /// (Re)load the domain list from disk, returns how many domains are on it.
+    /// Without a list configured only the heuristics are used
+    pub fn reload(&self) -> io::Result<usize> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(0),
+        };
+
+        let domains = fs::read_to_string(path)?
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(lookalike::normalize)
+            .collect::<HashSet<_>>();
+        let count = domains.len();
+        *self.domains.write() = domains;
+        Ok(count)
+    }
+
+    /// Check all links in a piece of text, returns the first problematic one
+    pub fn scan(&self, text: &str) -> Option<PhishingMatch> {
+        extract_domains(text).iter().find_map(|domain| self.check(domain))
+    }
+
+    pub fn check(&self, domain: &str) -> Option<PhishingMatch> {
+        let domain = lookalike::normalize(domain);
+        if OFFICIAL_DOMAINS
+            .iter()
+            .any(|official| is_same_or_subdomain(&domain, official))
+        {
+            return None;
+        }
+
+        {
+            let domains = self.domains.read();
+            let mut current = domain.as_str();
+            loop {
+                if domains.contains(current) {
+                    return Some(PhishingMatch::Listed(domain));
+                }
+                match current.split_once('.') {
+                    // don't go all the way up to just the tld
+                    Some((_, parent)) if parent.contains('.') => current = parent,
+                    _ => break,
+                }
+            }
+        }
+
+        lookalike::impersonates(&domain).map(|target| PhishingMatch::Lookalike { domain, target })
+    }
+}
+
+fn is_same_or_subdomain(domain: &str, parent: &str) -> bool {
+    domain == parent || domain.strip_suffix(parent).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector(domains: &[&str]) -> PhishingDetector {
+        PhishingDetector {
+            path: None,
+            domains: RwLock::new(domains.iter().map(|domain| domain.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn flags_listed_domains_and_subdomains() {
+        let detector = detector(&["scam.ru"]);
+        assert!(matches!(detector.check("scam.ru"), Some(PhishingMatch::Listed(_))));
+        assert!(matches!(
+            detector.check("login.SCAM.ru"),
+            Some(PhishingMatch::Listed(_))
+        ));
+        assert!(detector.check("notscam.ru").is_none());
+        assert!(detector.check("ru").is_none());
+    }
+
+    #[test]
+    fn never_flags_discord() {
+        let detector = detector(&["discord.com"]);
+        assert!(detector.check("discord.com").is_none());
+        assert!(detector.check("cdn.discordapp.com").is_none());
+        assert!(detector.scan("https://discord.gift/abc").is_none());
+    }
+
+    #[test]
+    fn scans_links() {
+        let detector = detector(&[]);
+        match detector.scan("free nitro https://discord.com@dlscord.gift/claim") {
+            Some(PhishingMatch::Lookalike { domain, target }) => {
+                assert_eq!(domain, "dlscord.gift");
+                assert_eq!(target, "discord.gift");
+            }
+            _ => panic!("lookalike should have been flagged"),
+        }
+        assert!(detector.scan("https://example.com").is_none());
+    }
+}
@@ -16,6 +16,27 @@ pub enum GearBotLangKey {
 
     //Userinfo command
     UserinfoUser,
+    UserinfoBanned,
+    UserinfoBanUnknown,
+
+    //Raid command
+    RaidEnded,
+    RaidNotActive,
+
+    //Invites command
+    InvitesStats,
+    InvitesNone,
+
+    //Verification
+    VerificationPanelPosted,
+    VerificationNotConfigured,
+    VerificationSucceeded,
+    VerificationAlreadyVerified,
+    VerificationAccountTooNew,
+    VerificationChallenge,
+    VerificationWrongAnswer,
+    VerificationExpired,
+    VerificationFailed,
 
     //Ping command
     PingCalculating,
@@ -29,6 +50,7 @@ pub enum GearBotLangKey {
     MissingRequiredOption,
     InvalidOption,
     UnknownUser,
+    MissingPermissions,
 }
 
 impl GearBotLangKey {
@@ -50,6 +72,22 @@ impl GearBotLangKey {
             GearBotLangKey::Minutes => "minutes",
             GearBotLangKey::Seconds => "seconds",
             GearBotLangKey::UserinfoUser => "user_info_user",
+            GearBotLangKey::UserinfoBanned => "user_info_banned",
+            GearBotLangKey::UserinfoBanUnknown => "user_info_ban_unknown",
+            GearBotLangKey::RaidEnded => "raid_ended",
+            GearBotLangKey::RaidNotActive => "raid_not_active",
+            GearBotLangKey::InvitesStats => "invites_stats",
+            GearBotLangKey::InvitesNone => "invites_none",
+            GearBotLangKey::VerificationPanelPosted => "verification_panel_posted",
+            GearBotLangKey::VerificationNotConfigured => "verification_not_configured",
+            GearBotLangKey::VerificationSucceeded => "verification_succeeded",
+            GearBotLangKey::VerificationAlreadyVerified => "verification_already_verified",
+            GearBotLangKey::VerificationAccountTooNew => "verification_account_too_new",
+            GearBotLangKey::VerificationChallenge => "verification_challenge",
+            GearBotLangKey::VerificationWrongAnswer => "verification_wrong_answer",
+            GearBotLangKey::VerificationExpired => "verification_expired",
+            GearBotLangKey::VerificationFailed => "verification_failed",
+            GearBotLangKey::MissingPermissions => "missing_permissions",
         }
     }
 }
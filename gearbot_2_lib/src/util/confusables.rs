@@ -0,0 +1,92 @@
+/// Fold text into plain lowercase ascii where possible, so "dіscοrd" written with cyrillic and greek letters,
+/// fullwidth or "fancy" math letters all turn into "discord" again.
+/// Combining marks and zero width characters are dropped entirely
+pub fn fold_confusables(text: &str) -> String {
+    text.chars()
+        .filter(|c| !is_combining(*c) && !is_invisible(*c))
+        .flat_map(char::to_lowercase)
+        .map(fold_char)
+        .collect()
+}
+
+/// Remove all combining marks, these are what zalgo text is made of
+pub fn strip_combining(text: &str) -> String {
+    text.chars().filter(|c| !is_combining(*c)).collect()
+}
+
+/// Diacritics and other marks that get drawn on top of the character before them
+pub fn is_combining(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F | 0x0483..=0x0489 | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF | 0xFE20..=0xFE2F
+    )
+}
+
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}')
+}
+
+fn fold_char(c: char) -> char {
+    let code = c as u32;
+    let folded = match code {
+        // fullwidth ascii
+        0xFF01..=0xFF5E => char::from_u32(code - 0xFEE0),
+        // circled letters
+        0x24B6..=0x24CF => char::from_u32('a' as u32 + code - 0x24B6),
+        0x24D0..=0x24E9 => char::from_u32('a' as u32 + code - 0x24D0),
+        // bold, italic, script, ... math letters, these come in runs of upper and lower case alphabets
+        0x1D400..=0x1D6A3 => char::from_u32('a' as u32 + (code - 0x1D400) % 52 % 26),
+        _ => None,
+    };
+    if let Some(folded) = folded {
+        return folded.to_ascii_lowercase();
+    }
+
+    match c {
+        'а' | 'α' | 'ɑ' => 'a',
+        'ь' => 'b',
+        'с' | 'ϲ' => 'c',
+        'ԁ' | 'ɗ' => 'd',
+        'е' | 'ё' | 'ε' => 'e',
+        'ɡ' => 'g',
+        'һ' => 'h',
+        'і' | 'ї' | 'ι' | 'ı' | '1' | '!' | '|' => 'i',
+        'ј' => 'j',
+        'κ' | 'к' => 'k',
+        'ӏ' | 'ℓ' => 'l',
+        'ո' => 'n',
+        'о' | 'ο' | '0' => 'o',
+        'р' | 'ρ' => 'p',
+        'ԛ' => 'q',
+        'г' => 'r',
+        'ѕ' | '5' => 's',
+        'т' | 'τ' => 't',
+        'υ' | 'ս' => 'u',
+        'ν' | 'ѵ' => 'v',
+        'ԝ' | 'ѡ' => 'w',
+        'х' | 'χ' => 'x',
+        'у' | 'γ' => 'y',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_lookalike_letters() {
+        assert_eq!(fold_confusables("dіscοrd"), "discord");
+        assert_eq!(fold_confusables("ＤＩＳＣＯＲＤ"), "discord");
+        assert_eq!(fold_confusables("𝐝𝐢𝐬𝐜𝐨𝐫𝐝"), "discord");
+        assert_eq!(fold_confusables("ⓓⓘⓢⓒⓞⓡⓓ"), "discord");
+        assert_eq!(fold_confusables("d1sc0rd"), "discord");
+    }
+
+    #[test]
+    fn drops_marks_and_invisible_characters() {
+        assert_eq!(fold_confusables("di\u{200b}sc\u{0301}ord"), "discord");
+        assert_eq!(strip_combining("ze\u{0301}\u{0302}lgo"), "zelgo");
+        assert_eq!(fold_confusables("plain text"), "plain text");
+    }
+}
@@ -0,0 +1,87 @@
+//! Custom ids for the message components we send out, the api uses these to figure out what got clicked.
+//! They look like `v1:prefix:arg:arg`, the prefix decides what handles the click.
+
+/// The button on verification panels, answers to the challenge carry the index of the answer
+pub const VERIFY: &str = "verify";
+
+const VERSION: &str = "v1";
+const SEPARATOR: char = ':';
+/// Discord rejects anything longer
+pub const MAX_LENGTH: usize = 100;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomId {
+    pub prefix: String,
+    pub args: Vec<String>,
+}
+
+impl CustomId {
+    pub fn new(prefix: &str) -> Self {
+        CustomId {
+            prefix: prefix.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Add an argument, these can't contain the separator
+    pub fn arg(mut self, arg: impl ToString) -> Self {
+        let arg = arg.to_string();
+        debug_assert!(
+            !arg.contains(SEPARATOR),
+            "Custom id argument contains the separator: {}",
+            arg
+        );
+        self.args.push(arg);
+        self
+    }
+
+    pub fn encode(&self) -> String {
+        let encoded = [VERSION, self.prefix.as_str()]
+            .into_iter()
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(&SEPARATOR.to_string());
+        debug_assert!(encoded.len() <= MAX_LENGTH, "Custom id is too long: {}", encoded);
+        encoded
+    }
+
+    /// Nothing if the id is from a version we don't know how to read
+    pub fn parse(raw: &str) -> Option<Self> {
+        let mut parts = raw.split(SEPARATOR);
+        if parts.next()? != VERSION {
+            return None;
+        }
+
+        let prefix = parts.next().filter(|prefix| !prefix.is_empty())?;
+        Some(CustomId {
+            prefix: prefix.to_string(),
+            args: parts.map(str::to_string).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let custom_id = CustomId::new(VERIFY).arg(3).arg("abc");
+        let encoded = custom_id.encode();
+        assert_eq!(encoded, "v1:verify:3:abc");
+        assert_eq!(CustomId::parse(&encoded), Some(custom_id));
+
+        let custom_id = CustomId::new(VERIFY);
+        assert_eq!(custom_id.encode(), "v1:verify");
+        assert_eq!(CustomId::parse(&custom_id.encode()), Some(custom_id));
+    }
+
+    #[test]
+    fn rejects_unknown_ids() {
+        assert_eq!(CustomId::parse("verify"), None);
+        assert_eq!(CustomId::parse("v2:verify"), None);
+        assert_eq!(CustomId::parse("v1"), None);
+        assert_eq!(CustomId::parse("v1:"), None);
+        assert_eq!(CustomId::parse(""), None);
+    }
+}
@@ -1,8 +1,12 @@
 use twilight_embed_builder::image_source::ImageSourceUrlError;
 use twilight_embed_builder::EmbedError;
+use twilight_http::request::AuditLogReasonError;
 use twilight_http::response::DeserializeBodyError;
 use twilight_http::Error;
+use twilight_model::datetime::TimestampParseError;
+use twilight_model::guild::Permissions;
 use twilight_validate::message::MessageValidationError;
+use twilight_validate::request::ValidationError;
 
 use crate::datastore::DatastoreError;
 use crate::kafka::sender::KafkaSenderError;
@@ -14,6 +18,7 @@ pub enum GearError {
     InvalidOption(String),
     MissingOption(String),
     UnknownUser(UserId),
+    MissingPermissions(Permissions),
 
     //System errors
     Twilight(twilight_http::Error),
@@ -24,13 +29,19 @@ pub enum GearError {
     DeserializeBody(DeserializeBodyError),
     SourceImageUrl(ImageSourceUrlError),
     MessageValidation(MessageValidationError),
+    RequestValidation(ValidationError),
+    AuditLogReason(AuditLogReasonError),
+    Timestamp(TimestampParseError),
 }
 
 impl GearError {
     pub fn is_user_error(&self) -> bool {
         matches!(
             self,
-            GearError::InvalidOption(_) | GearError::MissingOption(_) | GearError::UnknownUser(_)
+            GearError::InvalidOption(_)
+                | GearError::MissingOption(_)
+                | GearError::UnknownUser(_)
+                | GearError::MissingPermissions(_)
         )
     }
 
@@ -55,6 +66,12 @@ impl GearError {
                 .build()
                 .to_string(),
 
+            GearError::MissingPermissions(permissions) => translator
+                .translate(lang_code, GearBotLangKey::MissingPermissions)
+                .arg("permissions", format!("{:?}", permissions))
+                .build()
+                .to_string(),
+
             // Default generic error for system issues
             _ => translator
                 .translate(lang_code, GearBotLangKey::GenericSystemError)
@@ -74,6 +91,9 @@ impl GearError {
             GearError::DeserializeBody(e) => format!("Failed to deserialize the api response body: {:?}", e),
             GearError::SourceImageUrl(e) => format!("Invalid source url in an embed: {}", e),
             GearError::MessageValidation(e) => format!("Failed to assemble a proper message to send: {}", e),
+            GearError::RequestValidation(e) => format!("Failed to assemble a proper request to send: {}", e),
+            GearError::AuditLogReason(e) => format!("Invalid audit log reason: {}", e),
+            GearError::Timestamp(e) => format!("Invalid timestamp: {}", e),
             // this isn't called for user errors
             _ => "SOMEONE FORGOT TO PROPERLY MAP THIS!".to_string(),
         }
@@ -127,3 +147,21 @@ impl From<MessageValidationError> for GearError {
         GearError::MessageValidation(e)
     }
 }
+
+impl From<ValidationError> for GearError {
+    fn from(e: ValidationError) -> Self {
+        GearError::RequestValidation(e)
+    }
+}
+
+impl From<AuditLogReasonError> for GearError {
+    fn from(e: AuditLogReasonError) -> Self {
+        GearError::AuditLogReason(e)
+    }
+}
+
+impl From<TimestampParseError> for GearError {
+    fn from(e: TimestampParseError) -> Self {
+        GearError::Timestamp(e)
+    }
+}
@@ -6,12 +6,15 @@ use tracing::{info, warn};
 use twilight_http::client::ClientBuilder;
 use twilight_http::Client;
 use twilight_model::channel::message::AllowedMentions;
+use twilight_model::datetime::Timestamp;
 use twilight_util::snowflake::Snowflake;
 
 use crate::translations::{GearBotLangKey, Translator};
 use crate::util::error::GearError;
 use crate::util::markers::ApplicationId;
 
+pub mod confusables;
+pub mod custom_id;
 pub mod error;
 pub mod markers;
 pub mod url;
@@ -63,6 +66,15 @@ pub fn snowflake_age(snowflake: &dyn Snowflake, max_parts: usize, lang: &str, tr
     translated_age(snowflake_timestamp(snowflake), max_parts, lang, translator)
 }
 
+pub fn timestamp_age(timestamp: &Timestamp, max_parts: usize, lang: &str, translator: &Translator) -> String {
+    translated_age(
+        DateTime::from_utc(NaiveDateTime::from_timestamp(timestamp.as_secs(), 0), Utc),
+        max_parts,
+        lang,
+        translator,
+    )
+}
+
 pub fn translated_age(old: DateTime<Utc>, max_parts: usize, lang: &str, translator: &Translator) -> String {
     let mut seconds = Utc::now().signed_duration_since(old).num_seconds();
     let mut parts = Vec::new();
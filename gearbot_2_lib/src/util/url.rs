@@ -1,4 +1,4 @@
-use crate::util::markers::{GuildId, UserId};
+use crate::util::markers::{EmojiId, GuildId, RoleId, UserId};
 use twilight_model::util::ImageHash;
 
 pub fn assemble_guild_avatar_url(guild_id: &GuildId, user_id: &UserId, avatar: &ImageHash) -> String {
@@ -14,3 +14,27 @@ pub fn assemble_user_avatar(user_id: &UserId, discriminator: u16, avatar: Option
         |avatar| format!("https://cdn.discordapp.com/avatars/{}/{}.png", user_id, avatar),
     )
 }
+
+pub fn assemble_role_icon_url(role_id: &RoleId, icon: &ImageHash) -> String {
+    format!("https://cdn.discordapp.com/role-icons/{}/{}.png", role_id, icon)
+}
+
+pub fn assemble_emoji_url(emoji_id: &EmojiId, animated: bool) -> String {
+    format!(
+        "https://cdn.discordapp.com/emojis/{}.{}",
+        emoji_id,
+        if animated { "gif" } else { "png" }
+    )
+}
+
+pub fn assemble_guild_icon_url(guild_id: &GuildId, icon: &ImageHash) -> String {
+    format!("https://cdn.discordapp.com/icons/{}/{}.png", guild_id, icon)
+}
+
+pub fn assemble_guild_banner_url(guild_id: &GuildId, banner: &ImageHash) -> String {
+    format!("https://cdn.discordapp.com/banners/{}/{}.png", guild_id, banner)
+}
+
+pub fn assemble_guild_splash_url(guild_id: &GuildId, splash: &ImageHash) -> String {
+    format!("https://cdn.discordapp.com/splashes/{}/{}.png", guild_id, splash)
+}
@@ -0,0 +1,13 @@
+create table infraction
+(
+    id         bigserial   not null primary key,
+    guild      bigint      not null,
+    target     bigint      not null,
+    moderator  bigint      null,
+    type       int         not null,
+    reason     bytea       null,
+    created_at timestamptz not null default now(),
+    active     bool        not null
+);
+
+create index infraction_guild_target on infraction (guild, target);
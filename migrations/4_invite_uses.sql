@@ -0,0 +1,10 @@
+create table invite_use
+(
+    guild     bigint      not null,
+    code      text        not null,
+    inviter   bigint      null,
+    member    bigint      not null,
+    joined_at timestamptz not null default now()
+);
+
+create index invite_use_guild_code on invite_use (guild, code);
@@ -0,0 +1,10 @@
+create table sticky_member
+(
+    id       bigserial   not null primary key,
+    guild    bigint      not null,
+    member   bigint      not null,
+    roles    bigint[]    not null,
+    nickname bytea       null,
+    left_at  timestamptz not null default now(),
+    unique (guild, member)
+);
@@ -0,0 +1,5 @@
+user_info_user={"**"}{-user_id(case: "uppercase")}{"**"}: {$id}
+    {"**"}{-account_created}{"**"}: {$created_on}
+    {"**"}{-account_age}{"**"}: {$age}
+user_info_banned={"**"}Banned{"**"}: this user is banned from this server
+user_info_ban_unknown={"**"}Banned{"**"}: unknown, I can't see the bans of this server
@@ -0,0 +1,19 @@
+-user_id={$case ->
+     [uppercase] User id
+    *[lowercase] user id
+}
+-account_created=Account created on
+-account_age=Account age
+raid_ended=Raid mode ended, {$count} account(s) were involved in the raid.
+raid_not_active=There is no raid going on right now.
+invites_stats={$count} invite(s) have been used to join this server.
+invites_none=No joins through invites have been recorded for this server yet.
+verification_panel_posted=The verification panel has been posted.
+verification_not_configured=Verification is not set up for this server, enable it and configure a role first.
+verification_succeeded=You are now verified, welcome!
+verification_already_verified=You are already verified.
+verification_account_too_new=Your account is too new to verify, accounts need to be at least {$hours} hour(s) old.
+verification_challenge=Almost there! Click the {$thing}.
+verification_wrong_answer=That was not the right one, click the verify button to try again.
+verification_expired=This challenge expired, click the verify button to try again.
+verification_failed=Something went wrong while verifying you, please contact the server staff.
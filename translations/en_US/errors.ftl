@@ -0,0 +1,3 @@
+generic_system_error=A system error occurred, please try again later or report this issue on the support server!
+missing_required_option=The `{$name}` option is required but wasn't filled in!
+missing_permissions=You need the following permissions to do this: {$permissions}
\ No newline at end of file
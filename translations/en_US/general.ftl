@@ -0,0 +1,28 @@
+years={$count ->
+     [one] 1 year
+    *[other] {$count} years
+}
+months={$count ->
+     [one] 1 month
+    *[other] {$count} months
+}
+weeks={$count ->
+     [one] 1 week
+    *[other] {$count} weeks
+}
+days={$count ->
+     [one] 1 day
+    *[other] {$count} days
+}
+hours={$count ->
+     [one] 1 week
+    *[other] {$count} weeks
+}
+minutes={$count ->
+     [one] 1 minute
+    *[other] {$count} minutes
+}
+seconds={$count ->
+     [one] 1 second
+    *[other] {$count} seconds
+}
\ No newline at end of file